@@ -0,0 +1,119 @@
+//Общий замер для бенчмарков encmodes, imito, kdf и sign (cargo run --release -- bench в каждом крейте)
+//Каждый замер: прогрев и калибровка числа вызовов на выборку, затем от 5 до 30 выборок (около 0.3 с);
+//выводится среднее время с 95% доверительным интервалом (распределение Стьюдента), МБ/с и такты на байт.
+//Такты считываются rdtsc (только x86_64) - это опорная частота процессора, а не частота ядра под нагрузкой
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SAMPLE_TIME: Duration = Duration::from_millis(2);
+const MEASURE_TIME: Duration = Duration::from_millis(300);
+pub const MIN_SAMPLES: u128 = 5;
+const MAX_SAMPLES: u128 = 30;
+pub const MAX_SIZE: usize = 16 << 20;
+
+pub struct Stats {
+    //среднее время вызова и полуширина 95% интервала, нс
+    pub mean: f64,
+    pub ci95: f64,
+    //такты на вызов
+    pub cycles: Option<f64>,
+    pub samples: usize,
+}
+
+//квантиль t_{0.975} распределения Стьюдента для df степеней свободы
+fn t_975(df: usize) -> f64 {
+    const T: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160, 2.145, 2.131,
+        2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+    ];
+    T.get(df.wrapping_sub(1)).copied().unwrap_or(1.960)
+}
+
+#[allow(unused_unsafe)]
+fn cycles_now() -> Option<u64> {
+    #[cfg(target_arch = "x86_64")]
+    return Some(unsafe { core::arch::x86_64::_rdtsc() });
+    #[cfg(not(target_arch = "x86_64"))]
+    None
+}
+
+fn mean(xs: &[f64]) -> f64 {
+    xs.iter().sum::<f64>() / xs.len() as f64
+}
+
+pub fn measure<T, F: FnMut() -> T>(f: F) -> Stats {
+    measure_with_min_samples(MIN_SAMPLES, f)
+}
+
+//то же с другим минимумом выборок - для операций с большим разбросом (генерация ключей)
+pub fn measure_with_min_samples<T, F: FnMut() -> T>(min_samples: u128, mut f: F) -> Stats {
+    let start = Instant::now();
+    black_box(f());
+    let once = start.elapsed().as_nanos().max(1);
+    let iters = (SAMPLE_TIME.as_nanos() / once).max(1);
+    let samples = (MEASURE_TIME.as_nanos() / (once * iters)).clamp(min_samples, MAX_SAMPLES.max(min_samples)) as usize;
+    let mut times = Vec::with_capacity(samples);
+    let mut cycles = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        let c0 = cycles_now();
+        let t0 = Instant::now();
+        for _ in 0..iters {
+            black_box(f());
+        }
+        times.push(t0.elapsed().as_nanos() as f64 / iters as f64);
+        if let (Some(c0), Some(c1)) = (c0, cycles_now()) {
+            cycles.push(c1.wrapping_sub(c0) as f64 / iters as f64);
+        }
+    }
+    let m = mean(&times);
+    let var = times.iter().map(|t| (t - m) * (t - m)).sum::<f64>() / (samples - 1) as f64;
+    Stats {
+        mean: m,
+        ci95: t_975(samples - 1) * (var / samples as f64).sqrt(),
+        cycles: (!cycles.is_empty()).then(|| mean(&cycles)),
+        samples,
+    }
+}
+
+pub fn fmt_ns(ns: f64) -> String {
+    match ns {
+        ns if ns < 1e3 => format!("{:.0} нс", ns),
+        ns if ns < 1e6 => format!("{:.2} мкс", ns / 1e3),
+        ns if ns < 1e9 => format!("{:.2} мс", ns / 1e6),
+        ns => format!("{:.2} с", ns / 1e9),
+    }
+}
+
+pub fn fmt_size(bytes: usize) -> String {
+    match bytes {
+        b if b >= 1 << 20 => format!("{} МБ", b >> 20),
+        b if b >= 1 << 10 => format!("{} КБ", b >> 10),
+        b => format!("{} Б", b),
+    }
+}
+
+//строка отчёта для операции над сообщением: время, МБ/с с интервалом, такты на байт
+pub fn report_throughput(name: &str, bytes: usize, s: &Stats) {
+    let mbps = |ns: f64| bytes as f64 / ns * 1e9 / (1 << 20) as f64;
+    let cpb = s.cycles.map(|c| format!("{:.1}", c / bytes as f64)).unwrap_or_else(|| "-".to_string());
+    println!(
+        "{:<22} {:>6}  {:>10} ± {:<10} {:>8.1} МБ/с [{:.1}; {:.1}]  {} такт/байт",
+        name, fmt_size(bytes), fmt_ns(s.mean), fmt_ns(s.ci95), mbps(s.mean),
+        mbps(s.mean + s.ci95), mbps((s.mean - s.ci95).max(1.0)), cpb
+    );
+}
+
+//строка отчёта для операции без длины сообщения (KDF, генерация ключей, подпись)
+pub fn report_op(name: &str, s: &Stats) {
+    let cycles = s.cycles.map(|c| format!("{:.0}", c)).unwrap_or_else(|| "-".to_string());
+    println!(
+        "{:<22} {:>10} ± {:<10} {:.1} оп/с  {} тактов  ({} выборок)",
+        name, fmt_ns(s.mean), fmt_ns(s.ci95), 1e9 / s.mean, cycles, s.samples
+    );
+}
+
+//размеры сообщений от 16 Б до max_size, по умолчанию до 16 МБ
+pub fn sizes(args: &[String]) -> Vec<usize> {
+    let max_size = args.first().and_then(|s| s.parse().ok()).unwrap_or(MAX_SIZE);
+    [16, 256, 4 << 10, 64 << 10, 1 << 20, 16 << 20].into_iter().filter(|&n| n <= max_size).collect()
+}
@@ -0,0 +1,216 @@
+//Режимы с внутренней сменой ключа CTR-ACPKM и OMAC-ACPKM (Р 1323565.1.017-2018, RFC 8645)
+//Ключ секции меняется преобразованием ACPKM через каждые section_size байт,
+//чтобы ограничить объём данных, обрабатываемых на одном ключе.
+//ctr::SeekableCtr и mac::omac здесь не подходят: они работают на одном ключе и только с 16-байтным блоком,
+//а ACPKM пересоздаёт шифр на каждой секции и нужен также для Магмы (блок 8 байт)
+use crate::{BlockCipher, SecretKey};
+use crate::gost::{Kuznyechik, Magma};
+use aes::Aes256;
+use cipher::KeyInit;
+
+//шифр с 256-битным ключом, который можно пересоздать на выработанном ключе секции
+pub trait AcpkmCipher<const N: usize>: BlockCipher<N> + Sized {
+    fn from_key(key: &[u8; 32]) -> Self;
+}
+
+impl AcpkmCipher<16> for Kuznyechik {
+    fn from_key(key: &[u8; 32]) -> Self {
+        Kuznyechik::new(key)
+    }
+}
+
+impl AcpkmCipher<8> for Magma {
+    fn from_key(key: &[u8; 32]) -> Self {
+        Magma::new(key)
+    }
+}
+
+impl AcpkmCipher<16> for Aes256 {
+    fn from_key(key: &[u8; 32]) -> Self {
+        Aes256::new(key.into())
+    }
+}
+
+//ACPKM: K' = MSB_256(E_K(D_1) || E_K(D_2) || ...), D = 0x80 || 0x81 || ... || 0x9F
+pub fn acpkm<C: BlockCipher<N>, const N: usize>(cipher: &C) -> [u8; 32] {
+    let mut new_key = [0u8; 32];
+    for (j, chunk) in new_key.chunks_mut(N).enumerate() {
+        let mut d = [0u8; N];
+        for (i, byte) in d.iter_mut().enumerate() {
+            *byte = 0x80 + (j * N + i) as u8;
+        }
+        chunk.copy_from_slice(&cipher.encrypt(&d));
+    }
+    new_key
+}
+
+//блок счётчика IV || counter (для N = 16 это то же, что generate_nc), счёт по модулю 2^(n/2)
+fn ctr_block<const N: usize>(iv: &[u8], counter: u64) -> [u8; N] {
+    let mut block = [0u8; N];
+    block[..N / 2].copy_from_slice(iv);
+    block[N / 2..].copy_from_slice(&counter.to_be_bytes()[8 - N / 2..]);
+    block
+}
+
+//шифрование CTR-ACPKM; iv длины n/2, section_size кратен размеру блока
+//расшифрование выполняется той же функцией
+pub fn encrypt_ctr_acpkm<C: AcpkmCipher<N>, const N: usize>(
+    key: &[u8; 32],
+    iv: &[u8],
+    section_size: usize,
+    data: &[u8],
+) -> Vec<u8> {
+    assert_eq!(iv.len(), N / 2, "длина iv для CTR-ACPKM - половина блока");
+    assert!(section_size > 0 && section_size.is_multiple_of(N), "размер секции должен быть кратен блоку");
+    let mut res = Vec::with_capacity(data.len());
+    let mut cipher = C::from_key(key);
+    let mut counter = 0u64;
+    for (s, section) in data.chunks(section_size).enumerate() {
+        if s > 0 {
+            cipher = C::from_key(&acpkm(&cipher));
+        }
+        for block in section.chunks(N) {
+            let encrypt_block = cipher.encrypt(&ctr_block::<N>(iv, counter));
+            res.extend(block.iter().zip(encrypt_block.iter()).map(|(a, b)| a ^ b));
+            counter = counter.wrapping_add(1);
+        }
+    }
+    res
+}
+
+pub fn decrypt_ctr_acpkm<C: AcpkmCipher<N>, const N: usize>(
+    key: &[u8; 32],
+    iv: &[u8],
+    section_size: usize,
+    data: &[u8],
+) -> Vec<u8> {
+    encrypt_ctr_acpkm::<C, N>(key, iv, section_size, data)
+}
+
+//умножение на x в GF(2^n) для выработки K2 из K1 (как temp() в imito, но и для 64-битного блока)
+fn double<const N: usize>(block: &[u8; N]) -> [u8; N] {
+    let r = if N == 16 { 0x87 } else { 0x1B };
+    let mut out = [0u8; N];
+    let mut carry = 0u8;
+    for i in (0..N).rev() {
+        out[i] = (block[i] << 1) | carry;
+        carry = block[i] >> 7;
+    }
+    out[N - 1] ^= r & 0u8.wrapping_sub(carry);
+    out
+}
+
+//OMAC-ACPKM: ключи секций K^i и подключи K1^i берутся из ACPKM-Master -
+//CTR-ACPKM с размером секции master_section_size и iv = 1^(n/2) над нулевой строкой
+pub fn omac_acpkm<C: AcpkmCipher<N>, const N: usize>(
+    key: &[u8; 32],
+    section_size: usize,
+    master_section_size: usize,
+    data: &[u8],
+) -> [u8; N] {
+    assert!(section_size > 0 && section_size.is_multiple_of(N), "размер секции должен быть кратен блоку");
+    let sections = data.len().div_ceil(section_size).max(1);
+    let material = encrypt_ctr_acpkm::<C, N>(key, &vec![0xFF; N / 2], master_section_size, &vec![0u8; (32 + N) * sections]);
+    let section_keys: Vec<(C, [u8; N])> = material
+        .chunks(32 + N)
+        .map(|chunk| {
+            let k: [u8; 32] = chunk[..32].try_into().unwrap();
+            let k1: [u8; N] = chunk[32..].try_into().unwrap();
+            (C::from_key(&k), k1)
+        })
+        .collect();
+
+    let blocks = data.len().div_ceil(N).max(1);
+    let blocks_per_section = section_size / N;
+    let mut result = [0u8; N];
+    //все блоки кроме последнего - на ключе своей секции
+    for (i, block) in data.chunks(N).take(blocks - 1).enumerate() {
+        for j in 0..N {
+            result[j] ^= block[j];
+        }
+        result = section_keys[i / blocks_per_section].0.encrypt(&result);
+    }
+    //последний блок: полный -> XOR с K1, неполный -> паддинг 10... и XOR с K2 = K1 * x
+    let (last_cipher, k1) = &section_keys[(blocks - 1) / blocks_per_section];
+    let last = &data[(blocks - 1) * N..];
+    let mut last_block = [0u8; N];
+    last_block[..last.len()].copy_from_slice(last);
+    let subkey = if last.len() == N {
+        *k1
+    } else {
+        last_block[last.len()] = 0x80;
+        double(k1)
+    };
+    for j in 0..N {
+        result[j] ^= last_block[j] ^ subkey[j];
+    }
+    last_cipher.encrypt(&result)
+}
+
+//шифрование Кузнечик-CTR-ACPKM (iv 8 байт)
+pub fn encrypt_kuznyechik_ctr_acpkm(data: &[u8], key: &SecretKey<32>, iv: &[u8; 8], section_size: usize) -> Vec<u8> {
+    encrypt_ctr_acpkm::<Kuznyechik, 16>(key.expose_secret(), iv, section_size, data)
+}
+
+//расшифрование Кузнечик-CTR-ACPKM
+pub fn decrypt_kuznyechik_ctr_acpkm(data: &[u8], key: &SecretKey<32>, iv: &[u8; 8], section_size: usize) -> Vec<u8> {
+    decrypt_ctr_acpkm::<Kuznyechik, 16>(key.expose_secret(), iv, section_size, data)
+}
+
+//имитовставка Кузнечик-OMAC-ACPKM
+pub fn kuznyechik_omac_acpkm(key: &SecretKey<32>, data: &[u8], section_size: usize, master_section_size: usize) -> Vec<u8> {
+    omac_acpkm::<Kuznyechik, 16>(key.expose_secret(), section_size, master_section_size, data).to_vec()
+}
+
+//контрольные примеры CTR-ACPKM и OMAC-ACPKM из Р 1323565.1.017-2018 (они же в приложении A RFC 8645)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "8899aabbccddeeff0011223344556677fedcba98765432100123456789abcdef";
+    const P: &str = "1122334455667700ffeeddccbbaa998800112233445566778899aabbcceeff0a112233445566778899aabbcceeff0a002233445566778899aabbcceeff0a001133445566778899aabbcceeff0a001122445566778899aabbcceeff0a001122335566778899aabbcceeff0a0011223344";
+
+    fn key() -> SecretKey<32> {
+        SecretKey::from_slice(&hex::decode(KEY).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_ctr_acpkm_kuznyechik_vector() {
+        let iv: [u8; 8] = hex::decode("1234567890abcef0").unwrap().try_into().unwrap();
+        let p = hex::decode(P).unwrap();
+        let c = encrypt_kuznyechik_ctr_acpkm(&p, &key(), &iv, 32);
+        assert_eq!(hex::encode(&c), "f195d8bec10ed1dbd57b5fa240bda1b885eee733f6a13e5df33ce4b33c45dee44bceeb8f646f4c55001706275e85e800587c4df568d094393e4834afd0805046cf30f57686aeece11cfc6c316b8a896edffd07ec813636460c4f3b743423163e6409a9c282fac8d469d221e7fbd6de5d");
+        assert_eq!(decrypt_kuznyechik_ctr_acpkm(&c, &key(), &iv, 32), p);
+    }
+
+    #[test]
+    fn test_omac_acpkm_kuznyechik_sections() {
+        let p = hex::decode(P).unwrap();
+        //контрольный пример OMAC-ACPKM из Р 1323565.1.017-2018 (RFC 8645, A.2): N = 256 бит, T* = 768 бит, 5 блоков
+        assert_eq!(hex::encode(kuznyechik_omac_acpkm(&key(), &p[..80], 32, 96)), "fbb8dcee45bea67c35f58c5700898e5d");
+        //сообщение из одной секции: сдвиг секций дальше конца сообщения не влияет на тэг
+        let one_section = kuznyechik_omac_acpkm(&key(), &p[..32], 32, 96);
+        assert_eq!(one_section, kuznyechik_omac_acpkm(&key(), &p[..32], 64, 96));
+        //для пяти блоков разбиение на секции по 32 байта меняет ключи и, значит, тэг
+        let sectioned = kuznyechik_omac_acpkm(&key(), &p[..80], 32, 96);
+        assert_eq!(sectioned.len(), 16);
+        assert_ne!(sectioned, kuznyechik_omac_acpkm(&key(), &p[..80], 80, 96));
+        //полный и неполный последний блок обрабатываются разными подключами
+        assert_ne!(kuznyechik_omac_acpkm(&key(), &p[..15], 32, 96), kuznyechik_omac_acpkm(&key(), &p[..16], 32, 96));
+    }
+
+    #[test]
+    fn test_ctr_acpkm_changes_key_per_section() {
+        let key = [7u8; 32];
+        let iv = [1u8; 4];
+        let data = vec![0u8; 64];
+        //с секцией во весь текст ACPKM не применяется, а с секцией 16 байт ключ меняется на второй секции
+        let whole = encrypt_ctr_acpkm::<Magma, 8>(&key, &iv, 64, &data);
+        let sectioned = encrypt_ctr_acpkm::<Magma, 8>(&key, &iv, 16, &data);
+        assert_eq!(whole[..16], sectioned[..16]);
+        assert_ne!(whole[16..32], sectioned[16..32]);
+        assert_eq!(decrypt_ctr_acpkm::<Magma, 8>(&key, &iv, 16, &sectioned), data);
+        let tag = omac_acpkm::<Magma, 8>(&key, 16, 64, b"short message");
+        assert_ne!(tag, omac_acpkm::<Magma, 8>(&key, 16, 64, b"short messagE"));
+    }
+}
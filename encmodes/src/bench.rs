@@ -0,0 +1,99 @@
+//Бенчмарки: cargo run --release -- bench [максимальный размер сообщения в байтах]
+//Сам замер (выборки, 95% интервал, такты) - в крейте benchkit.
+//Шифрование и расшифрование меряются отдельно; функции encrypt_aes192_* расширяют ключ на каждый вызов,
+//это входит в замер; строки "с контекстом" - те же режимы через CbcCipher, CtrCipher и Omac,
+//где ключ расширен заранее (разница видна на коротких сообщениях)
+use crate::*;
+use benchkit::{fmt_size, measure, report_op, report_throughput, sizes, Stats};
+use std::hint::black_box;
+
+const GOST_MAX_SIZE: usize = 1 << 20;
+
+pub fn run(args: &[String]) {
+    let key = generate_key();
+    let iv = generate_iv();
+    let iv_ctr = generate_iv_ctr();
+    let nonce = [7u8; 12];
+    let xts_key = SecretKey::<48>::generate();
+    let key32 = SecretKey::from([0x42; 32]);
+    let kuznyechik = gost::Kuznyechik::new(&[0x42; 32]);
+    let mut mgm_nonce = [0x11u8; 16];
+    mgm_nonce[0] &= 0x7f;
+    let aes = aes::Aes192::new(key.expose_secret().into());
+    let cbc = CbcCipher::new(&key);
+    let ctr = CtrCipher::new(&key);
+    let omac = mac::aes192_omac(&key);
+
+    for size in sizes(args) {
+        let data: Vec<u8> = (0..size).map(|i| i as u8).collect();
+        println!("--- {} ---", fmt_size(size));
+        let report = |name: &str, s: Stats| report_throughput(name, size, &s);
+
+        let c = encrypt_aes192_ecb(&data, &key);
+        report("ecb шифрование", measure(|| encrypt_aes192_ecb(&data, &key)));
+        report("ecb расшифрование", measure(|| decrypt_aes192_ecb(&c, &key)));
+        let c = encrypt_aes192_cbc(&data, &key, &iv);
+        report("cbc шифрование", measure(|| encrypt_aes192_cbc(&data, &key, &iv)));
+        report("cbc расшифрование", measure(|| decrypt_aes192_cbc(&c, &key, &iv)));
+        report("cbc с контекстом", measure(|| cbc.encrypt(&data, &iv)));
+        let c = encrypt_aes192_cfb(&data, &key, &iv);
+        report("cfb шифрование", measure(|| encrypt_aes192_cfb(&data, &key, &iv)));
+        report("cfb расшифрование", measure(|| decrypt_aes192_cfb(&c, &key, &iv)));
+        report("cfb8", measure(|| cfb_ofb::encrypt_aes192_cfb_s(&data, &key, &iv, 8)));
+        let c = encrypt_aes192_ofb(&data, &key, &iv);
+        report("ofb шифрование", measure(|| encrypt_aes192_ofb(&data, &key, &iv)));
+        report("ofb расшифрование", measure(|| decrypt_aes192_ofb(&c, &key, &iv)));
+        let c = encrypt_aes192_ctr(&data, &key, &iv_ctr);
+        report("ctr шифрование", measure(|| encrypt_aes192_ctr(&data, &key, &iv_ctr)));
+        report("ctr расшифрование", measure(|| decrypt_aes192_ctr(&c, &key, &iv_ctr)));
+        report("ctr с контекстом", measure(|| ctr.encrypt(&data, &iv_ctr)));
+        //масштабирование по потокам 1, 2, 4, ... до числа ядер; на коротких сообщениях лишние потоки
+        //не запускаются (меньше MIN_BLOCKS_PER_THREAD блоков на поток), поэтому только от 1 МБ
+        if size >= 1 << 20 {
+            let mut threads = 1;
+            while threads <= parallel::default_threads() {
+                let name = format!("ctr потоков {}", threads);
+                report(&name, measure(|| parallel::par_encrypt_aes192_ctr(&data, &key, &iv_ctr, threads)));
+                threads *= 2;
+            }
+        }
+        report("cbc-cs3", measure(|| cbc_cs::encrypt_aes192_cbc_cs(&data, &key, &iv, cbc_cs::CsVariant::Cs3).unwrap()));
+        report("xts", measure(|| xts::encrypt_aes192_xts(&data, &xts_key, 0).unwrap()));
+
+        let c = gcm::encrypt_aes192_gcm(&data, &key, &nonce, b"");
+        report("gcm шифрование", measure(|| gcm::encrypt_aes192_gcm(&data, &key, &nonce, b"")));
+        report("gcm расшифрование", measure(|| gcm::decrypt_aes192_gcm(&c, &key, &nonce, b"")));
+        //13-байтовый nonce encrypt_aes192_ccm ограничивает сообщение 64 КБ, здесь 11 байт (до 4 ГБ)
+        report("ccm", measure(|| ccm::encrypt_ccm(&aes, &nonce[..11], b"", &data, 16)));
+        report("eax", measure(|| eax::encrypt_aes192_eax(&data, &key, &nonce, b"")));
+        report("ocb", measure(|| ocb::encrypt_aes192_ocb(&data, &key, &nonce, b"")));
+        report("siv", measure(|| siv::encrypt_aes192_siv(&data, &xts_key, &[])));
+        //сегменты по 64 КБ: на коротких сообщениях то же, что gcm, плюс последний сегмент
+        report("stream (gcm)", measure(|| stream::encrypt_aes192_stream(&data, &key, stream::AeadMode::Gcm, &[0; 7], 64 << 10, b"")));
+        report("chacha20", measure(|| chacha20::encrypt_chacha20(&data, &key32, &nonce, 1)));
+        report("chacha20-poly1305", measure(|| chacha20::encrypt_chacha20_poly1305(&data, &key32, &nonce, b"")));
+        report("aes-256-gcm-siv", measure(|| gcm_siv::encrypt_aes256_gcm_siv(&data, &key32, &nonce, b"")));
+        //табличный Кузнечик около 1 МБ/с: 16 МБ заняли бы минуты, а картину не меняют
+        if size <= GOST_MAX_SIZE {
+            report("кузнечик mgm", measure(|| mgm::encrypt_kuznyechik_mgm(&data, &key32, &mgm_nonce, b"")));
+            report("кузнечик ctr-acpkm", measure(|| acpkm::encrypt_kuznyechik_ctr_acpkm(&data, &key32, &[0x24; 8], 4096)));
+            report("кузнечик cfb", measure(|| cfb_ofb::encrypt_cfb(&kuznyechik, &[0x24; 16], 128, &data)));
+        }
+
+        report("omac", measure(|| mac::omac(&aes, &data)));
+        report("omac с контекстом", measure(|| omac.mac(&data)));
+        report("hmac-sha256", measure(|| pbe::hmac_sha256(key.expose_secret(), &data)));
+    }
+
+    println!("--- отдельные операции ---");
+    let block = [0x5au8; 16];
+    report_op("aes-192 ключ", &measure(|| aes::Aes192::new(black_box(key.expose_secret()).into())));
+    report_op("aes-192 блок", &measure(|| aes192_encrypt_block(&aes, black_box(&block))));
+    report_op("pbkdf2 (1000 итераций)", &measure(|| pbe::pbkdf2_hmac_sha256(b"password", b"salt", 1000, 48)));
+    report_op("kw (24 байта)", &measure(|| kw::wrap_aes192_kw(key.expose_secret(), &key).unwrap()));
+    let ff1 = fpe::Ff1::new(&key, 10).unwrap();
+    let ff3 = fpe::Ff3_1::new(&key, 10).unwrap();
+    let card = fpe::to_numerals("4111111111111111", fpe::DIGITS).unwrap();
+    report_op("ff1 (16 цифр)", &measure(|| ff1.encrypt(&card, b"")));
+    report_op("ff3-1 (16 цифр)", &measure(|| ff3.encrypt(&card, &[0; 7])));
+}
@@ -0,0 +1,242 @@
+//Известные ответы (KAT) в формате NIST CAVP .rsp для режимов ECB, CBC, CFB8, CFB128, OFB и CTR
+//Формат: "# ..." - комментарии, "[ENCRYPT]" / "[DECRYPT]" - секции, записи "ИМЯ = значение" разделены пустой строкой.
+//Файлы лежат в vectors/ и встраиваются в программу; длина ключа берётся из длины KEY, так что один файл
+//может содержать векторы для AES-128, AES-192 и AES-256. Шифрование в режимах всегда дополняет PKCS7,
+//поэтому сравнивается начало шифртекста длиной с ожидаемый.
+//CTR из файла проверяется через SeekableCtr: в SP 800-38A счётчик начинается с f8f9fafbfcfdfeff, а CtrCipher
+//всегда считает iv || 0, 1, ..., поэтому CtrCipher::from_cipher на этих векторах проверяется только по началу гаммы
+//(test_ctr_cipher_keystream_prefix: блоки iv || счётчик шифруются ECB, уже проверенным по своему файлу)
+use crate::ctr::SeekableCtr;
+use crate::{cfb_ofb, BlockCipher, CbcCipher, CfbCipher, EcbCipher, OfbCipher};
+use aes::{Aes128, Aes192, Aes256};
+use cipher::KeyInit;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RspRecord {
+    pub section: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl RspRecord {
+    //имена полей в разных файлах пишутся по-разному (KEY и Key), регистр не учитывается
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    pub fn hex(&self, name: &str) -> Option<Vec<u8>> {
+        hex::decode(self.get(name)?).ok()
+    }
+}
+
+pub fn parse_rsp(text: &str) -> Vec<RspRecord> {
+    let mut records = Vec::new();
+    let mut section = String::new();
+    let mut fields = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('[') {
+            if !fields.is_empty() {
+                records.push(RspRecord { section: section.clone(), fields: std::mem::take(&mut fields) });
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.to_string();
+            }
+        } else if let Some((name, value)) = line.split_once('=').filter(|_| !line.starts_with('#')) {
+            fields.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    if !fields.is_empty() {
+        records.push(RspRecord { section, fields });
+    }
+    records
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KatMode {
+    Ecb,
+    Cbc,
+    Cfb8,
+    Cfb128,
+    Ofb,
+    Ctr,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KatOutcome {
+    Pass,
+    Fail,
+    //длина ключа или секция не поддерживаются
+    Skipped,
+}
+
+#[derive(Clone, Debug)]
+pub struct KatResult {
+    pub section: String,
+    pub count: String,
+    pub key_bits: usize,
+    pub outcome: KatOutcome,
+}
+
+//файлы из vectors/: (имя, режим, содержимое)
+pub const FILES: [(&str, KatMode, &str); 9] = [
+    ("ECB_SP800-38A.rsp", KatMode::Ecb, include_str!("../vectors/ECB_SP800-38A.rsp")),
+    ("ECBGFSbox.rsp", KatMode::Ecb, include_str!("../vectors/ECBGFSbox.rsp")),
+    ("CBC_SP800-38A.rsp", KatMode::Cbc, include_str!("../vectors/CBC_SP800-38A.rsp")),
+    ("CBCGFSbox.rsp", KatMode::Cbc, include_str!("../vectors/CBCGFSbox.rsp")),
+    ("CFB8_SP800-38A.rsp", KatMode::Cfb8, include_str!("../vectors/CFB8_SP800-38A.rsp")),
+    ("CFB128_SP800-38A.rsp", KatMode::Cfb128, include_str!("../vectors/CFB128_SP800-38A.rsp")),
+    ("CFB128GFSbox.rsp", KatMode::Cfb128, include_str!("../vectors/CFB128GFSbox.rsp")),
+    ("OFB_SP800-38A.rsp", KatMode::Ofb, include_str!("../vectors/OFB_SP800-38A.rsp")),
+    ("CTR_SP800-38A.rsp", KatMode::Ctr, include_str!("../vectors/CTR_SP800-38A.rsp")),
+];
+
+fn apply_mode<C: BlockCipher<16>>(cipher: C, mode: KatMode, encrypt: bool, iv: &[u8; 16], input: &[u8]) -> Vec<u8> {
+    match (mode, encrypt) {
+        (KatMode::Ecb, true) => EcbCipher::from_cipher(cipher).encrypt(input),
+        (KatMode::Ecb, false) => EcbCipher::from_cipher(cipher).decrypt(input),
+        (KatMode::Cbc, true) => CbcCipher::from_cipher(cipher).encrypt(input, iv),
+        (KatMode::Cbc, false) => CbcCipher::from_cipher(cipher).decrypt(input, iv),
+        (KatMode::Cfb128, true) => CfbCipher::from_cipher(cipher).encrypt(input, iv),
+        (KatMode::Cfb128, false) => CfbCipher::from_cipher(cipher).decrypt(input, iv),
+        (KatMode::Cfb8, true) => cfb_ofb::encrypt_cfb(&cipher, iv, 8, input),
+        (KatMode::Cfb8, false) => cfb_ofb::decrypt_cfb(&cipher, iv, 8, input),
+        (KatMode::Ofb, true) => OfbCipher::from_cipher(cipher).encrypt(input, iv),
+        (KatMode::Ofb, false) => OfbCipher::from_cipher(cipher).decrypt(input, iv),
+        //IV - полный начальный блок, счётчик - младшие 8 байт (как в SP 800-38A)
+        (KatMode::Ctr, _) => {
+            let mut buf = input.to_vec();
+            match SeekableCtr::new(cipher, *iv, 8, 8).apply_keystream(&mut buf) {
+                Ok(()) => buf,
+                Err(_) => Vec::new(),
+            }
+        }
+    }
+}
+
+fn run_record(mode: KatMode, record: &RspRecord) -> KatOutcome {
+    let encrypt = match record.section.as_str() {
+        "ENCRYPT" => true,
+        "DECRYPT" => false,
+        _ => return KatOutcome::Skipped,
+    };
+    let (input, expected) = if encrypt { ("PLAINTEXT", "CIPHERTEXT") } else { ("CIPHERTEXT", "PLAINTEXT") };
+    let (Some(key), Some(input), Some(expected)) = (record.hex("KEY"), record.hex(input), record.hex(expected)) else {
+        return KatOutcome::Fail;
+    };
+    //в ECB поля IV нет
+    let iv: [u8; 16] = match record.hex("IV").map(<[u8; 16]>::try_from) {
+        None => [0; 16],
+        Some(Ok(iv)) => iv,
+        Some(Err(_)) => return KatOutcome::Fail,
+    };
+    let output = match key.len() {
+        16 => apply_mode(Aes128::new_from_slice(&key).unwrap(), mode, encrypt, &iv, &input),
+        24 => apply_mode(Aes192::new_from_slice(&key).unwrap(), mode, encrypt, &iv, &input),
+        32 => apply_mode(Aes256::new_from_slice(&key).unwrap(), mode, encrypt, &iv, &input),
+        _ => return KatOutcome::Skipped,
+    };
+    if output.len() >= expected.len() && output[..expected.len()] == expected[..] {
+        KatOutcome::Pass
+    } else {
+        KatOutcome::Fail
+    }
+}
+
+pub fn run_kat(mode: KatMode, text: &str) -> Vec<KatResult> {
+    parse_rsp(text)
+        .iter()
+        .map(|record| KatResult {
+            section: record.section.clone(),
+            count: record.get("COUNT").unwrap_or("?").to_string(),
+            key_bits: record.hex("KEY").map_or(0, |k| k.len() * 8),
+            outcome: run_record(mode, record),
+        })
+        .collect()
+}
+
+//cargo run -- kat: результат по каждому вектору и итог; false, если есть хоть одна ошибка
+pub fn run_all() -> bool {
+    let mut failed = 0;
+    for (name, mode, text) in FILES {
+        let results = run_kat(mode, text);
+        for r in &results {
+            println!("{} [{}] COUNT = {} AES-{}: {:?}", name, r.section, r.count, r.key_bits, r.outcome);
+        }
+        let passed = results.iter().filter(|r| r.outcome == KatOutcome::Pass).count();
+        failed += results.iter().filter(|r| r.outcome == KatOutcome::Fail).count();
+        println!("{}: пройдено {} из {}", name, passed, results.len());
+    }
+    failed == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CtrCipher;
+
+    #[test]
+    fn test_parse_rsp() {
+        let text = "# комментарий = не поле\n\n[ENCRYPT]\n\nCOUNT = 0\nKEY = 00ff\n\nCount = 1\nKey = 11\n[DECRYPT]\nCOUNT = 0\nKEY = 22";
+        let records = parse_rsp(text);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].section, "ENCRYPT");
+        assert_eq!(records[0].hex("key").unwrap(), [0x00, 0xff]);
+        assert_eq!(records[1].get("COUNT"), Some("1"));
+        assert_eq!(records[2].section, "DECRYPT");
+        assert_eq!(records[2].get("IV"), None);
+    }
+
+    //все векторы всех файлов проходят, и для каждого режима есть все три длины ключа в обе стороны
+    #[test]
+    fn test_all_files_pass() {
+        for (name, mode, text) in FILES {
+            let results = run_kat(mode, text);
+            assert!(!results.is_empty(), "{}", name);
+            for r in &results {
+                assert_eq!(r.outcome, KatOutcome::Pass, "{} [{}] COUNT = {}", name, r.section, r.count);
+            }
+            for bits in [128, 192, 256] {
+                for section in ["ENCRYPT", "DECRYPT"] {
+                    assert!(results.iter().any(|r| r.key_bits == bits && r.section == section), "{} AES-{}", name, bits);
+                }
+            }
+        }
+    }
+
+    //испорченный вектор отмечается как ошибка, неподдерживаемый ключ - как пропуск
+    #[test]
+    fn test_fail_and_skip() {
+        let text = "[ENCRYPT]\nCOUNT = 0\nKEY = 000102030405060708090a0b0c0d0e0f\nPLAINTEXT = 00112233445566778899aabbccddeeff\nCIPHERTEXT = 69c4e0d86a7b0430d8cdb78070b4c55a\n\n\
+                    COUNT = 1\nKEY = 000102030405060708090a0b0c0d0e0f\nPLAINTEXT = 00112233445566778899aabbccddeeff\nCIPHERTEXT = 69c4e0d86a7b0430d8cdb78070b4c55b\n\n\
+                    COUNT = 2\nKEY = 0001\nPLAINTEXT = 00\nCIPHERTEXT = 00";
+        let outcomes: Vec<KatOutcome> = run_kat(KatMode::Ecb, text).iter().map(|r| r.outcome).collect();
+        assert_eq!(outcomes, [KatOutcome::Pass, KatOutcome::Fail, KatOutcome::Skipped]);
+    }
+
+    //гамма CtrCipher::from_cipher на ключах и IV из CTR_SP800-38A.rsp (nonce - старшие 8 байт IV):
+    //начало гаммы совпадает с ECB-шифрованием блоков nonce || 0, nonce || 1, ...
+    fn check_ctr_prefix<C: BlockCipher<16> + Clone>(cipher: C, nonce: &[u8; 8], plaintext: &[u8]) {
+        let blocks: Vec<u8> = (0..plaintext.len().div_ceil(16) as u64).flat_map(|i| crate::generate_nc(*nonce, i)).collect();
+        let gamma = EcbCipher::from_cipher(cipher.clone()).encrypt(&blocks);
+        let expected: Vec<u8> = plaintext.iter().zip(&gamma).map(|(p, g)| p ^ g).collect();
+        let output = CtrCipher::from_cipher(cipher).decrypt(plaintext, nonce);
+        assert_eq!(output[..plaintext.len()], expected[..]);
+    }
+
+    #[test]
+    fn test_ctr_cipher_keystream_prefix() {
+        let records = parse_rsp(FILES[8].2);
+        let mut key_sizes = Vec::new();
+        for record in records.iter().filter(|r| r.section == "ENCRYPT") {
+            let (key, iv, plaintext) = (record.hex("KEY").unwrap(), record.hex("IV").unwrap(), record.hex("PLAINTEXT").unwrap());
+            let nonce: [u8; 8] = iv[..8].try_into().unwrap();
+            match key.len() {
+                16 => check_ctr_prefix(Aes128::new_from_slice(&key).unwrap(), &nonce, &plaintext),
+                24 => check_ctr_prefix(Aes192::new_from_slice(&key).unwrap(), &nonce, &plaintext),
+                32 => check_ctr_prefix(Aes256::new_from_slice(&key).unwrap(), &nonce, &plaintext),
+                _ => unreachable!(),
+            }
+            key_sizes.push(key.len());
+        }
+        assert_eq!(key_sizes, [16, 24, 32]);
+    }
+}
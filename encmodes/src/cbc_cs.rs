@@ -0,0 +1,180 @@
+//CBC с кражей шифртекста (дополнение к NIST SP 800-38A): CBC-CS1, CBC-CS2, CBC-CS3
+//В отличие от encrypt_aes192_cbc не дополняет сообщение, длина шифртекста равна длине открытого текста
+//Сообщение должно быть не короче одного блока, иначе CbcCsError::TooShort
+use crate::{BlockCipher, SecretKey};
+use aes::Aes192;
+use cipher::KeyInit;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CbcCsError {
+    //сообщение или шифртекст короче одного блока: красть шифртекст не из чего
+    TooShort,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsVariant {
+    //C_1 .. C_{n-2} || C*_{n-1} || C_n - неполный блок стоит на своём месте
+    Cs1,
+    //как CS3, если последний блок неполный, и обычный CBC иначе
+    Cs2,
+    //C_1 .. C_{n-2} || C_n || C*_{n-1} - последние два блока всегда переставлены (как в Kerberos, RFC 3962)
+    Cs3,
+}
+
+fn xor_block(a: &[u8; 16], b: &[u8; 16]) -> [u8; 16] {
+    let mut res = [0u8; 16];
+    for i in 0..16 {
+        res[i] = a[i] ^ b[i];
+    }
+    res
+}
+
+//нужно ли переставлять два последних блока при длине последнего блока d
+fn swapped(variant: CsVariant, d: usize) -> bool {
+    match variant {
+        CsVariant::Cs1 => false,
+        CsVariant::Cs2 => d != 16,
+        CsVariant::Cs3 => true,
+    }
+}
+
+pub fn encrypt_cbc_cs<C: BlockCipher<16>>(cipher: &C, iv: &[u8; 16], data: &[u8], variant: CsVariant) -> Result<Vec<u8>, CbcCsError> {
+    if data.len() < 16 {
+        return Err(CbcCsError::TooShort);
+    }
+    let n = data.len().div_ceil(16);
+    let d = data.len() - (n - 1) * 16;
+    //обычный CBC над сообщением, дополненным нулями
+    let mut res = Vec::with_capacity(n * 16);
+    let mut r = *iv;
+    for block in data.chunks(16) {
+        let mut block_array = [0u8; 16];
+        block_array[..block.len()].copy_from_slice(block);
+        r = cipher.encrypt(&xor_block(&block_array, &r));
+        res.extend_from_slice(&r);
+    }
+    if n == 1 {
+        return Ok(res);
+    }
+    //C*_{n-1} - первые d байт предпоследнего блока
+    let c_n = res.split_off((n - 1) * 16);
+    let c_prev = res.split_off((n - 2) * 16);
+    if swapped(variant, d) {
+        res.extend_from_slice(&c_n);
+        res.extend_from_slice(&c_prev[..d]);
+    } else {
+        res.extend_from_slice(&c_prev[..d]);
+        res.extend_from_slice(&c_n);
+    }
+    Ok(res)
+}
+
+pub fn decrypt_cbc_cs<C: BlockCipher<16>>(cipher: &C, iv: &[u8; 16], data: &[u8], variant: CsVariant) -> Result<Vec<u8>, CbcCsError> {
+    if data.len() < 16 {
+        return Err(CbcCsError::TooShort);
+    }
+    let n = data.len().div_ceil(16);
+    let d = data.len() - (n - 1) * 16;
+    if n == 1 {
+        return Ok(xor_block(&cipher.decrypt(data.try_into().unwrap()), iv).to_vec());
+    }
+    //приводим к порядку CS1: C_1 .. C_{n-2} || C*_{n-1} || C_n
+    let head = &data[..(n - 2) * 16];
+    let (c_prev_part, c_n): (&[u8], &[u8]) = if swapped(variant, d) {
+        (&data[(n - 2) * 16 + 16..], &data[(n - 2) * 16..(n - 2) * 16 + 16])
+    } else {
+        (&data[(n - 2) * 16..(n - 2) * 16 + d], &data[(n - 2) * 16 + d..])
+    };
+    //Z = D(C_n): первые d байт дают P*_n, остальные достраивают C_{n-1}
+    let z = cipher.decrypt(c_n.try_into().unwrap());
+    let mut c_prev = z;
+    c_prev[..d].copy_from_slice(c_prev_part);
+
+    let mut res = Vec::with_capacity(data.len());
+    let mut r = *iv;
+    for block in head.chunks(16).chain(std::iter::once(&c_prev[..])) {
+        let block_array: [u8; 16] = block.try_into().unwrap();
+        res.extend_from_slice(&xor_block(&cipher.decrypt(&block_array), &r));
+        r = block_array;
+    }
+    res.extend(z[..d].iter().zip(c_prev.iter()).map(|(a, b)| a ^ b));
+    Ok(res)
+}
+
+//шифрование AES192-CBC с кражей шифртекста
+pub fn encrypt_aes192_cbc_cs(data: &[u8], key: &SecretKey<24>, iv: &[u8; 16], variant: CsVariant) -> Result<Vec<u8>, CbcCsError> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    encrypt_cbc_cs(&cipher, iv, data, variant)
+}
+
+//расшифрование AES192-CBC с кражей шифртекста
+pub fn decrypt_aes192_cbc_cs(data: &[u8], key: &SecretKey<24>, iv: &[u8; 16], variant: CsVariant) -> Result<Vec<u8>, CbcCsError> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    decrypt_cbc_cs(&cipher, iv, data, variant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::Aes128;
+
+    //векторы AES-CTS из приложения B RFC 3962 (это CBC-CS3 с нулевым iv)
+    #[test]
+    fn test_cbc_cs3_rfc3962_vectors() {
+        let key: [u8; 16] = hex::decode("636869636b656e207465726979616b69").unwrap().try_into().unwrap();
+        let cipher = Aes128::new(&key.into());
+        let iv = [0u8; 16];
+        let vectors = [
+            ("4920776f756c64206c696b652074686520", "c6353568f2bf8cb4d8a580362da7ff7f97"),
+            ("4920776f756c64206c696b65207468652047656e6572616c20476175277320",
+             "fc00783e0efdb2c1d445d4c8eff7ed2297687268d6ecccc0c07b25e25ecfe5"),
+            ("4920776f756c64206c696b65207468652047656e6572616c2047617527732043",
+             "39312523a78662d5be7fcbcc98ebf5a897687268d6ecccc0c07b25e25ecfe584"),
+        ];
+        for (p, c) in vectors {
+            let p = hex::decode(p).unwrap();
+            let ciphertext = encrypt_cbc_cs(&cipher, &iv, &p, CsVariant::Cs3).unwrap();
+            assert_eq!(hex::encode(&ciphertext), c);
+            assert_eq!(decrypt_cbc_cs(&cipher, &iv, &ciphertext, CsVariant::Cs3).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn test_cbc_cs_variants_relations() {
+        let key = crate::generate_key();
+        let iv = crate::generate_iv();
+        let message = "Стояла зима. Дул ветер из степи.".as_bytes();
+        for len in [16, 17, 31, 32, 33, 47, 48, message.len()] {
+            let p = &message[..len];
+            let cs1 = encrypt_aes192_cbc_cs(p, &key, &iv, CsVariant::Cs1).unwrap();
+            let cs2 = encrypt_aes192_cbc_cs(p, &key, &iv, CsVariant::Cs2).unwrap();
+            let cs3 = encrypt_aes192_cbc_cs(p, &key, &iv, CsVariant::Cs3).unwrap();
+            assert_eq!(cs1.len(), len);
+            if len % 16 == 0 {
+                //при кратной длине CS1 и CS2 совпадают с CBC без дополнения
+                let cbc = crate::encrypt_aes192_cbc(p, &key, &iv);
+                assert_eq!(cs1, cbc[..len]);
+                assert_eq!(cs2, cs1);
+            } else {
+                assert_eq!(cs2, cs3);
+            }
+            for (variant, c) in [(CsVariant::Cs1, &cs1), (CsVariant::Cs2, &cs2), (CsVariant::Cs3, &cs3)] {
+                assert_eq!(decrypt_aes192_cbc_cs(c, &key, &iv, variant).unwrap(), p);
+            }
+        }
+    }
+
+    //сообщения и шифртексты короче блока - ошибка, а не паника
+    #[test]
+    fn test_cbc_cs_too_short() {
+        let key = crate::generate_key();
+        let iv = crate::generate_iv();
+        let data = [0x33u8; 15];
+        for len in 0..16 {
+            for variant in [CsVariant::Cs1, CsVariant::Cs2, CsVariant::Cs3] {
+                assert_eq!(encrypt_aes192_cbc_cs(&data[..len], &key, &iv, variant), Err(CbcCsError::TooShort));
+                assert_eq!(decrypt_aes192_cbc_cs(&data[..len], &key, &iv, variant), Err(CbcCsError::TooShort));
+            }
+        }
+    }
+}
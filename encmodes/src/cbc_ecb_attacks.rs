@@ -0,0 +1,198 @@
+//Учебные атаки на режимы без аутентификации:
+//  - подмена битов в CBC: P_i = D(C_i) ^ C_{i-1}, поэтому XOR в C_{i-1} переносится в P_i (ценой порчи P_{i-1});
+//  - "вырезать и вставить" в ECB: одинаковые блоки шифруются одинаково, блоки разных сообщений можно склеивать;
+//  - побайтовое расшифрование ECB: если атакующий управляет началом сообщения, неизвестный хвост читается
+//    по одному байту за не более чем 256 запросов
+//Оракулы построены на CbcCipher, EcbCipher и encrypt_aes192_ecb
+use crate::{encrypt_aes192_ecb, pkcs7_unpad, CbcCipher, EcbCipher, SecretKey};
+
+//ключ и iv сервисов генерируются один раз и атакующему неизвестны
+//"сервис" шифрует строку профиля с пользовательскими данными и проверяет, не админ ли владелец
+pub struct CbcProfileService {
+    cipher: CbcCipher,
+    iv: [u8; 16],
+}
+
+impl CbcProfileService {
+    pub const PREFIX: &'static str = "comment1=cooking%20MCs;userdata=";
+    pub const SUFFIX: &'static str = ";comment2=%20like%20a%20pound%20of%20bacon";
+
+    pub fn new() -> Self {
+        CbcProfileService { cipher: CbcCipher::new(&crate::generate_key()), iv: crate::generate_iv() }
+    }
+
+    //; и = в пользовательских данных экранируются, так что вписать ";admin=true" напрямую нельзя
+    pub fn encrypt_userdata(&self, userdata: &str) -> Vec<u8> {
+        let quoted = userdata.replace(';', "%3B").replace('=', "%3D");
+        let profile = format!("{}{}{}", Self::PREFIX, quoted, Self::SUFFIX);
+        self.cipher.encrypt(profile.as_bytes(), &self.iv)
+    }
+
+    pub fn is_admin(&self, ciphertext: &[u8]) -> bool {
+        let Some(plaintext) = pkcs7_unpad(&self.cipher.decrypt(ciphertext, &self.iv), 16) else {
+            return false;
+        };
+        //испорченный блок даёт мусор, но сервис разбирает строку побайтно
+        plaintext.split(|&b| b == b';').any(|field| field == b"admin=true")
+    }
+}
+
+impl Default for CbcProfileService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//подмена битов: открытый текст в позиции position (known) превращается в desired
+//изменяется предыдущий блок шифртекста, поэтому position должна быть не меньше 16
+pub fn cbc_bit_flip(ciphertext: &[u8], position: usize, known: &[u8], desired: &[u8]) -> Vec<u8> {
+    assert_eq!(known.len(), desired.len());
+    assert!(position >= 16 && position + known.len() <= ciphertext.len(), "правка должна попадать в блоки со второго");
+    let mut res = ciphertext.to_vec();
+    for i in 0..known.len() {
+        res[position - 16 + i] ^= known[i] ^ desired[i];
+    }
+    res
+}
+
+//атака на CbcProfileService: блок "заглушки" жертвуется, следующий блок становится ";admin=true"
+pub fn cbc_bit_flip_attack(service: &CbcProfileService) -> Vec<u8> {
+    let prefix_len = CbcProfileService::PREFIX.len();
+    //дополняем префикс до границы блока, затем целый блок-жертва и блок с правкой
+    let filler = (16 - prefix_len % 16) % 16 + 16;
+    let known = "?admin?true";
+    let userdata = format!("{}{}", "A".repeat(filler), known);
+    let ciphertext = service.encrypt_userdata(&userdata);
+    cbc_bit_flip(&ciphertext, prefix_len + filler, known.as_bytes(), b";admin=true")
+}
+
+//ECB-сервис профилей: email=...&uid=10&role=user
+pub struct EcbProfileService {
+    cipher: EcbCipher,
+}
+
+impl EcbProfileService {
+    pub fn new() -> Self {
+        EcbProfileService { cipher: EcbCipher::new(&crate::generate_key()) }
+    }
+
+    pub fn profile_for(&self, email: &str) -> Vec<u8> {
+        let email: String = email.chars().filter(|&c| c != '&' && c != '=').collect();
+        let profile = format!("email={}&uid=10&role=user", email);
+        self.cipher.encrypt(profile.as_bytes())
+    }
+
+    pub fn role(&self, ciphertext: &[u8]) -> Option<String> {
+        let plaintext = pkcs7_unpad(&self.cipher.decrypt(ciphertext), 16)?;
+        let profile = String::from_utf8(plaintext).ok()?;
+        profile.split('&').find_map(|kv| kv.strip_prefix("role=")).map(|r| r.to_string())
+    }
+}
+
+impl Default for EcbProfileService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//"вырезать и вставить": блок "admin" + дополнение из одного профиля приклеивается
+//к началу другого профиля, который кончается ровно на "role="
+pub fn ecb_cut_and_paste_attack(service: &EcbProfileService) -> Vec<u8> {
+    //"email=" + 10 байт - первый блок, второй блок - admin с дополнением PKCS7
+    let admin_block = format!("{}admin{}", "A".repeat(10), "\x0b".repeat(11));
+    let admin_ciphertext = service.profile_for(&admin_block);
+    //"email=" (6) + 13 байт + "&uid=10&role=" (13) = 32 байта
+    let head = service.profile_for("foo@bar.coooo");
+    let mut res = head[..32].to_vec();
+    res.extend_from_slice(&admin_ciphertext[16..32]);
+    res
+}
+
+//оракул "шифрование данных атакующего с неизвестным секретным хвостом"
+pub fn ecb_suffix_oracle(key: SecretKey<24>, secret: Vec<u8>) -> impl Fn(&[u8]) -> Vec<u8> {
+    move |input| {
+        let mut data = input.to_vec();
+        data.extend_from_slice(&secret);
+        encrypt_aes192_ecb(&data, &key)
+    }
+}
+
+//есть ли в шифртексте повторяющиеся блоки - признак ECB
+pub fn detect_ecb(ciphertext: &[u8], block_size: usize) -> bool {
+    let blocks: Vec<&[u8]> = ciphertext.chunks(block_size).collect();
+    (0..blocks.len()).any(|i| blocks[i + 1..].contains(&blocks[i]))
+}
+
+//побайтовое расшифрование секретного хвоста через оракул ECB
+pub fn ecb_byte_at_a_time_attack<F: Fn(&[u8]) -> Vec<u8>>(oracle: F) -> Option<Vec<u8>> {
+    //размер блока и длина секрета - по скачку длины шифртекста
+    let base_len = oracle(b"").len();
+    let mut pad = 1;
+    let jump_len = loop {
+        let len = oracle(&vec![b'A'; pad]).len();
+        if len > base_len {
+            break len;
+        }
+        pad += 1;
+    };
+    let block_size = jump_len - base_len;
+    let secret_len = base_len - pad;
+    if !detect_ecb(&oracle(&vec![b'A'; 3 * block_size]), block_size) {
+        return None;
+    }
+
+    let mut secret = Vec::with_capacity(secret_len);
+    for i in 0..secret_len {
+        //секретный байт i оказывается последним в блоке block
+        let filler = vec![b'A'; block_size - 1 - i % block_size];
+        let block = i / block_size;
+        let target = oracle(&filler);
+        let target_block = &target[block * block_size..(block + 1) * block_size];
+        //последние block_size - 1 известных байт + перебираемый байт
+        let mut probe = filler.clone();
+        probe.extend_from_slice(&secret);
+        let mut probe = probe[probe.len() + 1 - block_size..].to_vec();
+        probe.push(0);
+        let byte = (0..=255u8).find(|&b| {
+            probe[block_size - 1] = b;
+            oracle(&probe)[..block_size] == *target_block
+        })?;
+        secret.push(byte);
+    }
+    Some(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cbc_bit_flipping() {
+        let service = CbcProfileService::new();
+        //прямая попытка не проходит из-за экранирования
+        assert!(!service.is_admin(&service.encrypt_userdata(";admin=true")));
+        assert!(service.is_admin(&cbc_bit_flip_attack(&service)));
+    }
+
+    #[test]
+    fn test_ecb_cut_and_paste() {
+        let service = EcbProfileService::new();
+        assert_eq!(service.role(&service.profile_for("user@example.com&role=admin")).unwrap(), "user");
+        assert_eq!(service.role(&ecb_cut_and_paste_attack(&service)).unwrap(), "admin");
+    }
+
+    #[test]
+    fn test_ecb_byte_at_a_time() {
+        let secret = "Стояла зима. Дул ветер из степи.".as_bytes().to_vec();
+        let oracle = ecb_suffix_oracle(crate::generate_key(), secret.clone());
+        assert_eq!(ecb_byte_at_a_time_attack(oracle).unwrap(), secret);
+        //против CBC та же атака не работает: повторяющихся блоков нет
+        let key = crate::generate_key();
+        let cbc_oracle = |input: &[u8]| {
+            let mut data = input.to_vec();
+            data.extend_from_slice(&secret);
+            crate::encrypt_aes192_cbc(&data, &key, &crate::generate_iv())
+        };
+        assert!(ecb_byte_at_a_time_attack(cbc_oracle).is_none());
+    }
+}
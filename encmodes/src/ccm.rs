@@ -0,0 +1,200 @@
+//Режим CCM (RFC 3610, NIST SP 800-38C) - CBC-MAC от отформатированных данных + CTR
+//Длина nonce n от 7 до 13 байт, длина поля длины сообщения q = 15 - n,
+//длина тэга t - чётная от 4 до 16 байт
+use crate::mac::cbc_mac;
+use crate::{BlockCipher, SecretKey, ct_eq};
+use aes::Aes192;
+use cipher::KeyInit;
+
+fn check_params(nonce_len: usize, tag_len: usize) -> bool {
+    (7..=13).contains(&nonce_len) && (4..=16).contains(&tag_len) && tag_len.is_multiple_of(2)
+}
+
+//первый блок B0: флаги || N || Q
+pub fn ccm_format_b0(nonce: &[u8], aad_len: usize, data_len: usize, tag_len: usize) -> [u8; 16] {
+    let q = 15 - nonce.len();
+    assert!(q >= 8 || (data_len as u64) < (1u64 << (8 * q)), "сообщение слишком длинное для данной длины nonce");
+    let mut b0 = [0u8; 16];
+    let adata = if aad_len > 0 { 0x40 } else { 0 };
+    b0[0] = adata | ((((tag_len - 2) / 2) as u8) << 3) | ((q - 1) as u8);
+    b0[1..1 + nonce.len()].copy_from_slice(nonce);
+    let len_bytes = (data_len as u64).to_be_bytes();
+    let q_bytes = q.min(8);
+    b0[16 - q_bytes..].copy_from_slice(&len_bytes[8 - q_bytes..]);
+    b0
+}
+
+//кодирование ассоциированных данных: длина (2, 6 или 10 байт) || aad, дополнение нулями до блока
+pub fn ccm_format_aad(aad: &[u8]) -> Vec<u8> {
+    let mut res = Vec::new();
+    if aad.is_empty() {
+        return res;
+    }
+    let a = aad.len() as u64;
+    if a < (1 << 16) - (1 << 8) {
+        res.extend_from_slice(&(a as u16).to_be_bytes());
+    } else if a < (1 << 32) {
+        res.extend_from_slice(&[0xff, 0xfe]);
+        res.extend_from_slice(&(a as u32).to_be_bytes());
+    } else {
+        res.extend_from_slice(&[0xff, 0xff]);
+        res.extend_from_slice(&a.to_be_bytes());
+    }
+    res.extend_from_slice(aad);
+    res.resize(res.len().div_ceil(16) * 16, 0);
+    res
+}
+
+//блок счётчика Ctr_i: флаги (q - 1) || N || [i]_q
+pub fn ccm_counter_block(nonce: &[u8], i: u64) -> [u8; 16] {
+    let q = 15 - nonce.len();
+    let mut ctr = [0u8; 16];
+    ctr[0] = (q - 1) as u8;
+    ctr[1..1 + nonce.len()].copy_from_slice(nonce);
+    let i_bytes = i.to_be_bytes();
+    let q_bytes = q.min(8);
+    ctr[16 - q_bytes..].copy_from_slice(&i_bytes[8 - q_bytes..]);
+    ctr
+}
+
+//CBC-MAC от B0 || закодированные aad || сообщение с дополнением нулями
+fn ccm_mac<C: BlockCipher<16>>(cipher: &C, nonce: &[u8], aad: &[u8], data: &[u8], tag_len: usize) -> [u8; 16] {
+    let mut formatted = Vec::with_capacity(16 + aad.len() + data.len() + 48);
+    formatted.extend_from_slice(&ccm_format_b0(nonce, aad.len(), data.len(), tag_len));
+    formatted.extend_from_slice(&ccm_format_aad(aad));
+    formatted.extend_from_slice(data);
+    formatted.resize(formatted.len().div_ceil(16) * 16, 0);
+    cbc_mac(cipher, &formatted)
+}
+
+//CTR со счётчиками Ctr_1, Ctr_2, ... (Ctr_0 уходит на шифрование тэга)
+fn ccm_ctr<C: BlockCipher<16>>(cipher: &C, nonce: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(data.len());
+    for (i, block) in data.chunks(16).enumerate() {
+        let encrypt_block = cipher.encrypt(&ccm_counter_block(nonce, i as u64 + 1));
+        res.extend(block.iter().zip(encrypt_block.iter()).map(|(a, b)| a ^ b));
+    }
+    res
+}
+
+//шифрование CCM, возвращает (шифртекст, тэг длины tag_len байт)
+pub fn encrypt_ccm<C: BlockCipher<16>>(
+    cipher: &C,
+    nonce: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    tag_len: usize,
+) -> (Vec<u8>, Vec<u8>) {
+    assert!(check_params(nonce.len(), tag_len), "недопустимые длины nonce или тэга для CCM");
+    let t = ccm_mac(cipher, nonce, aad, data, tag_len);
+    let s0 = cipher.encrypt(&ccm_counter_block(nonce, 0));
+    let tag = t.iter().zip(s0.iter()).take(tag_len).map(|(a, b)| a ^ b).collect();
+    (ccm_ctr(cipher, nonce, data), tag)
+}
+
+//расшифрование CCM, при несовпадении тэга возвращает None
+pub fn decrypt_ccm<C: BlockCipher<16>>(
+    cipher: &C,
+    nonce: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    tag: &[u8],
+) -> Option<Vec<u8>> {
+    if !check_params(nonce.len(), tag.len()) {
+        return None;
+    }
+    let q = 15 - nonce.len();
+    if q < 8 && (data.len() as u64) >= (1u64 << (8 * q)) {
+        return None;
+    }
+    let plaintext = ccm_ctr(cipher, nonce, data);
+    let t = ccm_mac(cipher, nonce, aad, &plaintext, tag.len());
+    let s0 = cipher.encrypt(&ccm_counter_block(nonce, 0));
+    let expected: Vec<u8> = t.iter().zip(s0.iter()).take(tag.len()).map(|(a, b)| a ^ b).collect();
+    if !ct_eq(&expected, tag) {
+        return None;
+    }
+    Some(plaintext)
+}
+
+//шифрование AES192-CCM с nonce 13 байт: на выходе шифртекст || тэг 16 байт
+pub fn encrypt_aes192_ccm(data: &[u8], key: &SecretKey<24>, nonce: &[u8; 13], aad: &[u8]) -> Vec<u8> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    let (mut res, tag) = encrypt_ccm(&cipher, nonce, aad, data, 16);
+    res.extend_from_slice(&tag);
+    res
+}
+
+//расшифрование AES192-CCM: на входе шифртекст || тэг 16 байт
+pub fn decrypt_aes192_ccm(data: &[u8], key: &SecretKey<24>, nonce: &[u8; 13], aad: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 16 {
+        return None;
+    }
+    let cipher = Aes192::new(key.expose_secret().into());
+    let (ciphertext, tag) = data.split_at(data.len() - 16);
+    decrypt_ccm(&cipher, nonce, aad, ciphertext, tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::Aes128;
+
+    fn check(key: &str, nonce: &str, aad: &str, p: &str, c: &str, t: &str) {
+        let key: [u8; 16] = hex::decode(key).unwrap().try_into().unwrap();
+        let cipher = Aes128::new(&key.into());
+        let nonce = hex::decode(nonce).unwrap();
+        let aad = hex::decode(aad).unwrap();
+        let p = hex::decode(p).unwrap();
+        let (ciphertext, tag) = encrypt_ccm(&cipher, &nonce, &aad, &p, t.len() / 2);
+        assert_eq!(hex::encode(&ciphertext), c);
+        assert_eq!(hex::encode(&tag), t);
+        assert_eq!(decrypt_ccm(&cipher, &nonce, &aad, &ciphertext, &tag).unwrap(), p);
+    }
+
+    //примеры C.1 - C.3 из NIST SP 800-38C
+    #[test]
+    fn test_ccm_sp800_38c_vectors() {
+        let key = "404142434445464748494a4b4c4d4e4f";
+        check(key, "10111213141516", "0001020304050607", "20212223", "7162015b", "4dac255d");
+        check(key, "1011121314151617", "000102030405060708090a0b0c0d0e0f",
+              "202122232425262728292a2b2c2d2e2f", "d2a1f0e051ea5f62081a7792073d593d", "1fc64fbfaccd");
+        check(key, "101112131415161718191a1b", "000102030405060708090a0b0c0d0e0f10111213",
+              "202122232425262728292a2b2c2d2e2f3031323334353637",
+              "e3b201a9f5b71a7a9b1ceaeccd97e70b6176aad9a4428aa5", "484392fbc1b09951");
+    }
+
+    //Packet Vector #1 и #2 из RFC 3610
+    #[test]
+    fn test_ccm_rfc3610_vectors() {
+        let key = "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf";
+        check(key, "00000003020100a0a1a2a3a4a5", "0001020304050607",
+              "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e",
+              "588c979a61c663d2f066d0c2c0f989806d5f6b61dac384", "17e8d12cfdf926e0");
+        check(key, "00000004030201a0a1a2a3a4a5", "0001020304050607",
+              "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+              "72c91a36e135f8cf291ca894085c87e3cc15c439c9e43a3b", "a091d56e10400916");
+    }
+
+    #[test]
+    fn test_ccm_format_aad_lengths() {
+        assert!(ccm_format_aad(&[]).is_empty());
+        let short = ccm_format_aad(&[1u8; 3]);
+        assert_eq!(&short[..5], &[0, 3, 1, 1, 1]);
+        assert_eq!(short.len(), 16);
+        let long = ccm_format_aad(&vec![0u8; 0xff00]);
+        assert_eq!(&long[..6], &[0xff, 0xfe, 0, 0, 0xff, 0]);
+    }
+
+    #[test]
+    fn test_aes192_ccm_round_trip_and_tamper() {
+        let key = crate::generate_key();
+        let nonce = [7u8; 13];
+        let ciphertext = encrypt_aes192_ccm(b"meter reading 42", &key, &nonce, b"device-1");
+        assert_eq!(decrypt_aes192_ccm(&ciphertext, &key, &nonce, b"device-1").unwrap(), b"meter reading 42");
+        assert!(decrypt_aes192_ccm(&ciphertext, &key, &nonce, b"device-2").is_none());
+        let mut broken = ciphertext.clone();
+        broken[3] ^= 0x10;
+        assert!(decrypt_aes192_ccm(&broken, &key, &nonce, b"device-1").is_none());
+    }
+}
@@ -0,0 +1,199 @@
+//CFB и OFB с настраиваемыми размером сегмента s и длиной регистра сдвига m (NIST SP 800-38A, ГОСТ Р 34.13-2015)
+//Регистр задаётся начальным значением iv длины m (не меньше блока), шифруются старшие n бит регистра
+//CFB: C_i = P_i ^ MSB_s(E(MSB_n(R))), R = LSB_{m-s}(R) || C_i
+//OFB: Y_i = E(MSB_n(R)), C_i = P_i ^ MSB_s(Y_i), R = LSB_{m-n}(R) || Y_i
+//Режимы поточные: сообщение не дополняется, последний сегмент может быть неполным
+use crate::{BlockCipher, SecretKey};
+use aes::Aes192;
+use cipher::KeyInit;
+
+fn get_bit(data: &[u8], i: usize) -> u8 {
+    (data[i / 8] >> (7 - i % 8)) & 1
+}
+
+fn set_bit(data: &mut [u8], i: usize, bit: u8) {
+    data[i / 8] |= bit << (7 - i % 8);
+}
+
+//сдвиг регистра влево на s бит, в младшие разряды попадают старшие s бит segment
+fn shift_register(reg: &mut [u8], segment: &[u8], s: usize) {
+    if s.is_multiple_of(8) {
+        let k = s / 8;
+        let len = reg.len();
+        reg.copy_within(k.., 0);
+        reg[len - k..].copy_from_slice(&segment[..k]);
+        return;
+    }
+    for i in 0..s {
+        let mut carry = get_bit(segment, i);
+        for b in reg.iter_mut().rev() {
+            let next = *b >> 7;
+            *b = (*b << 1) | carry;
+            carry = next;
+        }
+    }
+}
+
+//наложение s-битного сегмента гаммы на данные с позиции pos (в битах); возвращает сегмент шифртекста
+fn xor_segment(data: &[u8], res: &mut [u8], gamma: &[u8], pos: usize, s: usize, encrypt: bool) -> Vec<u8> {
+    let mut segment = vec![0u8; s.div_ceil(8)];
+    if s.is_multiple_of(8) {
+        let start = pos / 8;
+        let end = (start + s / 8).min(data.len());
+        for i in start..end {
+            res[i] = data[i] ^ gamma[i - start];
+        }
+        let cipher_segment = if encrypt { &res[start..end] } else { &data[start..end] };
+        segment[..end - start].copy_from_slice(cipher_segment);
+        return segment;
+    }
+    let len = s.min(data.len() * 8 - pos);
+    for i in 0..len {
+        let p = get_bit(data, pos + i);
+        let c = p ^ get_bit(gamma, i);
+        set_bit(res, pos + i, c);
+        set_bit(&mut segment, i, if encrypt { c } else { p });
+    }
+    segment
+}
+
+fn cfb_process<C: BlockCipher<N>, const N: usize>(cipher: &C, iv: &[u8], segment_bits: usize, data: &[u8], encrypt: bool) -> Vec<u8> {
+    assert!(iv.len() >= N, "регистр сдвига CFB не короче блока");
+    assert!((1..=8 * N).contains(&segment_bits), "размер сегмента от 1 бита до длины блока");
+    let mut reg = iv.to_vec();
+    let mut res = vec![0u8; data.len()];
+    let mut pos = 0;
+    while pos < data.len() * 8 {
+        let gamma = cipher.encrypt(reg[..N].try_into().unwrap());
+        let segment = xor_segment(data, &mut res, &gamma, pos, segment_bits, encrypt);
+        shift_register(&mut reg, &segment, segment_bits);
+        pos += segment_bits;
+    }
+    res
+}
+
+//шифрование CFB-s; iv - начальное заполнение регистра длины m байт
+pub fn encrypt_cfb<C: BlockCipher<N>, const N: usize>(cipher: &C, iv: &[u8], segment_bits: usize, data: &[u8]) -> Vec<u8> {
+    cfb_process(cipher, iv, segment_bits, data, true)
+}
+
+pub fn decrypt_cfb<C: BlockCipher<N>, const N: usize>(cipher: &C, iv: &[u8], segment_bits: usize, data: &[u8]) -> Vec<u8> {
+    cfb_process(cipher, iv, segment_bits, data, false)
+}
+
+//OFB-s: регистр длины m = z * n сдвигается на целый блок, от каждого блока гаммы берутся старшие s бит
+//шифрование и расшифрование совпадают
+pub fn ofb<C: BlockCipher<N>, const N: usize>(cipher: &C, iv: &[u8], segment_bits: usize, data: &[u8]) -> Vec<u8> {
+    assert!(!iv.is_empty() && iv.len().is_multiple_of(N), "регистр сдвига OFB кратен длине блока");
+    assert!((1..=8 * N).contains(&segment_bits), "размер сегмента от 1 бита до длины блока");
+    let mut reg = iv.to_vec();
+    let mut res = vec![0u8; data.len()];
+    let mut pos = 0;
+    while pos < data.len() * 8 {
+        let gamma = cipher.encrypt(reg[..N].try_into().unwrap());
+        xor_segment(data, &mut res, &gamma, pos, segment_bits, true);
+        shift_register(&mut reg, &gamma, 8 * N);
+        pos += segment_bits;
+    }
+    res
+}
+
+//AES192-CFB с сегментом segment_bits бит (1, 8, 64, 128 ...), без дополнения
+pub fn encrypt_aes192_cfb_s(data: &[u8], key: &SecretKey<24>, iv: &[u8; 16], segment_bits: usize) -> Vec<u8> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    encrypt_cfb(&cipher, iv, segment_bits, data)
+}
+
+pub fn decrypt_aes192_cfb_s(data: &[u8], key: &SecretKey<24>, iv: &[u8; 16], segment_bits: usize) -> Vec<u8> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    decrypt_cfb(&cipher, iv, segment_bits, data)
+}
+
+//AES192-OFB с сегментом segment_bits бит, без дополнения
+pub fn encrypt_aes192_ofb_s(data: &[u8], key: &SecretKey<24>, iv: &[u8; 16], segment_bits: usize) -> Vec<u8> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    ofb(&cipher, iv, segment_bits, data)
+}
+
+pub fn decrypt_aes192_ofb_s(data: &[u8], key: &SecretKey<24>, iv: &[u8; 16], segment_bits: usize) -> Vec<u8> {
+    encrypt_aes192_ofb_s(data, key, iv, segment_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gost::Kuznyechik;
+
+    const KEY: &str = "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b";
+    const IV: &str = "000102030405060708090a0b0c0d0e0f";
+
+    fn check(segment_bits: usize, ofb_mode: bool, p: &str, c: &str) {
+        let key = SecretKey::<24>::from_slice(&hex::decode(KEY).unwrap()).unwrap();
+        let iv: [u8; 16] = hex::decode(IV).unwrap().try_into().unwrap();
+        let p = hex::decode(p).unwrap();
+        let (ciphertext, plaintext) = if ofb_mode {
+            let c = encrypt_aes192_ofb_s(&p, &key, &iv, segment_bits);
+            let p = decrypt_aes192_ofb_s(&c, &key, &iv, segment_bits);
+            (c, p)
+        } else {
+            let c = encrypt_aes192_cfb_s(&p, &key, &iv, segment_bits);
+            let p = decrypt_aes192_cfb_s(&c, &key, &iv, segment_bits);
+            (c, p)
+        };
+        assert_eq!(hex::encode(&ciphertext), c);
+        assert_eq!(plaintext, p);
+    }
+
+    //векторы F.3 и F.4 из NIST SP 800-38A для AES-192
+    #[test]
+    fn test_sp800_38a_aes192_vectors() {
+        //CFB1: биты 0110101111000001 -> 1001001101011001
+        check(1, false, "6bc1", "9359");
+        check(8, false, "6bc1bee22e409f96e93d7e117393172aae2d", "cda2521ef0a905ca44cd057cbf0d47a0678a");
+        check(128, false, "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51",
+              "cdc80d6fddf18cab34c25909c99a417467ce7f7f81173621961a2b70171d3d7a");
+        check(128, true, "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51",
+              "cdc80d6fddf18cab34c25909c99a4174fcc28b8d4c63837c09e81700c1100401");
+    }
+
+    //примеры ГОСТ Р 34.13-2015 для "Кузнечика": s = n, m = 2n
+    #[test]
+    fn test_gost_34_13_kuznyechik_vectors() {
+        let key: [u8; 32] = hex::decode("8899aabbccddeeff0011223344556677fedcba98765432100123456789abcdef").unwrap().try_into().unwrap();
+        let cipher = Kuznyechik::new(&key);
+        let iv = hex::decode("1234567890abcef0a1b2c3d4e5f0011223344556677889901213141516171819").unwrap();
+        let p = hex::decode(concat!(
+            "1122334455667700ffeeddccbbaa9988", "00112233445566778899aabbcceeff0a",
+            "112233445566778899aabbcceeff0a00", "2233445566778899aabbcceeff0a0011"
+        )).unwrap();
+        let c = ofb(&cipher, &iv, 128, &p);
+        assert_eq!(hex::encode(&c), concat!(
+            "81800a59b1842b24ff1f795e897abd95", "ed5b47a7048cfab48fb521369d9326bf",
+            "66a257ac3ca0b8b1c80fe7fc10288a13", "203ebbc066138660a0292243f6903150"
+        ));
+        assert_eq!(ofb(&cipher, &iv, 128, &c), p);
+        let c = encrypt_cfb(&cipher, &iv, 128, &p);
+        assert_eq!(hex::encode(&c), concat!(
+            "81800a59b1842b24ff1f795e897abd95", "ed5b47a7048cfab48fb521369d9326bf",
+            "79f2a8eb5cc68d38842d264e97a238b5", "4ffebecd4e922de6c75bd9dd44fbf4d1"
+        ));
+        assert_eq!(decrypt_cfb(&cipher, &iv, 128, &c), p);
+    }
+
+    #[test]
+    fn test_cfb_ofb_segment_round_trip() {
+        let key = crate::generate_key();
+        let iv = crate::generate_iv();
+        let message = "Свеча горела на столе".as_bytes();
+        for s in [1, 3, 8, 64, 100, 128] {
+            let c = encrypt_aes192_cfb_s(message, &key, &iv, s);
+            assert_eq!(c.len(), message.len());
+            assert_eq!(decrypt_aes192_cfb_s(&c, &key, &iv, s), message);
+            let c = encrypt_aes192_ofb_s(message, &key, &iv, s);
+            assert_eq!(decrypt_aes192_ofb_s(&c, &key, &iv, s), message);
+        }
+        //CFB128 совпадает с encrypt_aes192_cfb без последнего блока дополнения
+        let full = crate::encrypt_aes192_cfb(&message[..32], &key, &iv);
+        assert_eq!(encrypt_aes192_cfb_s(&message[..32], &key, &iv, 128), full[..32]);
+    }
+}
@@ -0,0 +1,282 @@
+//Потоковый шифр ChaCha20, имитовставка Poly1305 и AEAD ChaCha20-Poly1305 по RFC 8439
+//Нужны там, где нет аппаратного AES: только сложения, XOR и сдвиги 32-битных слов, без таблиц
+//Ключ 32 байта, nonce 96 бит, счётчик блоков 32 бита (не больше 256 ГБ на один nonce)
+use crate::SecretKey;
+
+//"expand 32-byte k"
+const SIGMA: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+fn le32(b: &[u8]) -> u32 {
+    u32::from_le_bytes(b[..4].try_into().unwrap())
+}
+
+fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+//блок гаммы: 10 двойных раундов (по столбцам и по диагоналям) и сложение с исходным состоянием
+pub fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&SIGMA);
+    for i in 0..8 {
+        state[4 + i] = le32(&key[4 * i..]);
+    }
+    state[12] = counter;
+    for i in 0..3 {
+        state[13 + i] = le32(&nonce[4 * i..]);
+    }
+    let mut working = state;
+    for _ in 0..10 {
+        quarter_round(&mut working, 0, 4, 8, 12);
+        quarter_round(&mut working, 1, 5, 9, 13);
+        quarter_round(&mut working, 2, 6, 10, 14);
+        quarter_round(&mut working, 3, 7, 11, 15);
+        quarter_round(&mut working, 0, 5, 10, 15);
+        quarter_round(&mut working, 1, 6, 11, 12);
+        quarter_round(&mut working, 2, 7, 8, 13);
+        quarter_round(&mut working, 3, 4, 9, 14);
+    }
+    let mut res = [0u8; 64];
+    for i in 0..16 {
+        res[4 * i..4 * i + 4].copy_from_slice(&working[i].wrapping_add(state[i]).to_le_bytes());
+    }
+    res
+}
+
+//шифрование ChaCha20 с начальным значением счётчика counter; дополнение не нужно
+pub fn encrypt_chacha20(data: &[u8], key: &SecretKey<32>, nonce: &[u8; 12], counter: u32) -> Vec<u8> {
+    let blocks = data.len().div_ceil(64) as u64;
+    assert!(counter as u64 + blocks <= 1 << 32, "счётчик ChaCha20 переполнится: слишком длинное сообщение");
+    let mut res = Vec::with_capacity(data.len());
+    for (i, chunk) in data.chunks(64).enumerate() {
+        let keystream = chacha20_block(key.expose_secret(), counter.wrapping_add(i as u32), nonce);
+        res.extend(chunk.iter().zip(keystream.iter()).map(|(d, k)| d ^ k));
+    }
+    res
+}
+
+//расшифрование совпадает с шифрованием
+pub fn decrypt_chacha20(data: &[u8], key: &SecretKey<32>, nonce: &[u8; 12], counter: u32) -> Vec<u8> {
+    encrypt_chacha20(data, key, nonce, counter)
+}
+
+//Poly1305: h = (h + блок || 0x01) * r mod 2^130 - 5 по 16 байт, в конце tag = (h + s) mod 2^128
+//Числа хранятся пятью 26-битными частями, чтобы произведения помещались в u64
+pub fn poly1305(key: &[u8; 32], msg: &[u8]) -> [u8; 16] {
+    const MASK: u32 = 0x3ffffff;
+    //"зажатие" r: часть битов обнуляется по спецификации
+    let r0 = le32(&key[0..]) & 0x3ffffff;
+    let r1 = (le32(&key[3..]) >> 2) & 0x3ffff03;
+    let r2 = (le32(&key[6..]) >> 4) & 0x3ffc0ff;
+    let r3 = (le32(&key[9..]) >> 6) & 0x3f03fff;
+    let r4 = (le32(&key[12..]) >> 8) & 0x00fffff;
+    //2^130 = 5 по модулю p, поэтому старшие части сворачиваются умножением на 5
+    let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+    let mut h = [0u32; 5];
+
+    for chunk in msg.chunks(16) {
+        let mut block = [0u8; 17];
+        block[..chunk.len()].copy_from_slice(chunk);
+        block[chunk.len()] = 1;
+        //для полного блока единица - это 129-й бит (бит 24 в старшей части)
+        let hibit = (block[16] as u32) << 24;
+        h[0] += le32(&block[0..]) & MASK;
+        h[1] += (le32(&block[3..]) >> 2) & MASK;
+        h[2] += (le32(&block[6..]) >> 4) & MASK;
+        h[3] += (le32(&block[9..]) >> 6) & MASK;
+        h[4] += (le32(&block[12..]) >> 8) | hibit;
+
+        let m = |a: u32, b: u32| a as u64 * b as u64;
+        let d0 = m(h[0], r0) + m(h[1], s4) + m(h[2], s3) + m(h[3], s2) + m(h[4], s1);
+        let mut d1 = m(h[0], r1) + m(h[1], r0) + m(h[2], s4) + m(h[3], s3) + m(h[4], s2);
+        let mut d2 = m(h[0], r2) + m(h[1], r1) + m(h[2], r0) + m(h[3], s4) + m(h[4], s3);
+        let mut d3 = m(h[0], r3) + m(h[1], r2) + m(h[2], r1) + m(h[3], r0) + m(h[4], s4);
+        let mut d4 = m(h[0], r4) + m(h[1], r3) + m(h[2], r2) + m(h[3], r1) + m(h[4], r0);
+
+        d1 += d0 >> 26;
+        h[0] = d0 as u32 & MASK;
+        d2 += d1 >> 26;
+        h[1] = d1 as u32 & MASK;
+        d3 += d2 >> 26;
+        h[2] = d2 as u32 & MASK;
+        d4 += d3 >> 26;
+        h[3] = d3 as u32 & MASK;
+        h[4] = d4 as u32 & MASK;
+        h[0] += (d4 >> 26) as u32 * 5;
+        h[1] += h[0] >> 26;
+        h[0] &= MASK;
+    }
+
+    //полный перенос
+    for i in 1..5 {
+        h[i] += h[i - 1] >> 26;
+        h[i - 1] &= MASK;
+    }
+    h[0] += (h[4] >> 26) * 5;
+    h[4] &= MASK;
+    h[1] += h[0] >> 26;
+    h[0] &= MASK;
+
+    //g = h + 5 - 2^130; если g >= 0, то h >= p и результатом будет g (выбор по маске, без ветвлений)
+    let mut g = [0u32; 5];
+    let mut carry = 5;
+    for i in 0..5 {
+        let t = h[i] + carry;
+        carry = t >> 26;
+        g[i] = t & MASK;
+    }
+    g[4] = g[4].wrapping_add(carry << 26).wrapping_sub(1 << 26);
+    let use_g = (g[4] >> 31).wrapping_sub(1);
+    for i in 0..5 {
+        h[i] = (h[i] & !use_g) | (g[i] & use_g);
+    }
+
+    //h mod 2^128 четырьмя словами и прибавление s
+    let words = [
+        h[0] | (h[1] << 26),
+        (h[1] >> 6) | (h[2] << 20),
+        (h[2] >> 12) | (h[3] << 14),
+        (h[3] >> 18) | (h[4] << 8),
+    ];
+    let mut tag = [0u8; 16];
+    let mut f = 0u64;
+    for i in 0..4 {
+        f = words[i] as u64 + le32(&key[16 + 4 * i..]) as u64 + (f >> 32);
+        tag[4 * i..4 * i + 4].copy_from_slice(&(f as u32).to_le_bytes());
+    }
+    tag
+}
+
+//одноразовый ключ Poly1305 - первые 32 байта блока ChaCha20 со счётчиком 0
+pub fn poly1305_key_gen(key: &[u8; 32], nonce: &[u8; 12]) -> [u8; 32] {
+    chacha20_block(key, 0, nonce)[..32].try_into().unwrap()
+}
+
+//aad || дополнение || шифртекст || дополнение || длины в байтах (little-endian)
+fn aead_tag(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+    let pad16 = |len: usize| vec![0u8; (16 - len % 16) % 16];
+    let mut mac_data = aad.to_vec();
+    mac_data.extend_from_slice(&pad16(aad.len()));
+    mac_data.extend_from_slice(ciphertext);
+    mac_data.extend_from_slice(&pad16(ciphertext.len()));
+    mac_data.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+    mac_data.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+    poly1305(&poly1305_key_gen(key, nonce), &mac_data)
+}
+
+//шифрование ChaCha20-Poly1305: на выходе шифртекст || тэг 16 байт
+pub fn encrypt_chacha20_poly1305(data: &[u8], key: &SecretKey<32>, nonce: &[u8; 12], aad: &[u8]) -> Vec<u8> {
+    let mut res = encrypt_chacha20(data, key, nonce, 1);
+    let tag = aead_tag(key.expose_secret(), nonce, aad, &res);
+    res.extend_from_slice(&tag);
+    res
+}
+
+//расшифрование ChaCha20-Poly1305: на входе шифртекст || тэг 16 байт, None при несовпадении тэга
+pub fn decrypt_chacha20_poly1305(data: &[u8], key: &SecretKey<32>, nonce: &[u8; 12], aad: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 16 {
+        return None;
+    }
+    let (ciphertext, tag) = data.split_at(data.len() - 16);
+    if !crate::ct_eq(&aead_tag(key.expose_secret(), nonce, aad, ciphertext), tag) {
+        return None;
+    }
+    Some(decrypt_chacha20(ciphertext, key, nonce, 1))
+}
+
+//тестовые векторы из RFC 8439
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUNSCREEN: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+    fn key_from(start: u8) -> [u8; 32] {
+        core::array::from_fn(|i| start + i as u8)
+    }
+
+    fn nonce(s: &str) -> [u8; 12] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    //2.3.2 и 2.4.2
+    #[test]
+    fn test_chacha20_vectors() {
+        let block = chacha20_block(&key_from(0), 1, &nonce("000000090000004a00000000"));
+        assert_eq!(hex::encode(block), concat!(
+            "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e",
+            "d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e"));
+
+        let key = SecretKey::from(key_from(0));
+        let n = nonce("000000000000004a00000000");
+        let c = encrypt_chacha20(SUNSCREEN, &key, &n, 1);
+        assert_eq!(hex::encode(&c), concat!(
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b",
+            "f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8",
+            "07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736",
+            "5af90bbf74a35be6b40b8eedf2785e42874d"));
+        assert_eq!(decrypt_chacha20(&c, &key, &n, 1), SUNSCREEN);
+    }
+
+    //2.5.2 и 2.6.2
+    #[test]
+    fn test_poly1305_vectors() {
+        let key: [u8; 32] = hex::decode("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+            .unwrap().try_into().unwrap();
+        assert_eq!(hex::encode(poly1305(&key, b"Cryptographic Forum Research Group")), "a8061dc1305136c6c22b8baf0c0127a9");
+        assert_eq!(hex::encode(poly1305_key_gen(&key_from(0x80), &nonce("000000000001020304050607"))),
+                   "8ad5a08b905f81cc815040274ab29471a833b637e3fd0da508dbb8e2fdd1a646");
+    }
+
+    //приложение A.3, векторы 5-9: переносы и финальное приведение по модулю 2^130 - 5
+    #[test]
+    fn test_poly1305_edge_cases() {
+        let key = |r: u8, s: u8| -> [u8; 32] { core::array::from_fn(|i| if i == 0 { r } else if i >= 16 { s } else { 0 }) };
+        let cat = |parts: &[&[u8]]| parts.concat();
+        let tag = |k: [u8; 32], m: &[u8]| hex::encode(poly1305(&k, m));
+        assert_eq!(tag(key(2, 0), &[0xff; 16]), "03000000000000000000000000000000");
+        let mut two = [0u8; 16];
+        two[0] = 2;
+        assert_eq!(tag(key(2, 0xff), &two), "03000000000000000000000000000000");
+        let mut tail = [0u8; 16];
+        tail[0] = 0x11;
+        let mut f0 = [0xffu8; 16];
+        f0[0] = 0xf0;
+        assert_eq!(tag(key(1, 0), &cat(&[&[0xff; 16], &f0, &tail])), "05000000000000000000000000000000");
+        let mut fb = [0xfeu8; 16];
+        fb[0] = 0xfb;
+        assert_eq!(tag(key(1, 0), &cat(&[&[0xff; 16], &fb, &[0x01; 16]])), "00000000000000000000000000000000");
+        let mut fd = [0xffu8; 16];
+        fd[0] = 0xfd;
+        assert_eq!(tag(key(2, 0), &fd), "faffffffffffffffffffffffffffffff");
+    }
+
+    //2.8.2
+    #[test]
+    fn test_aead_vector() {
+        let key = SecretKey::from(key_from(0x80));
+        let n = nonce("070000004041424344454647");
+        let aad = hex::decode("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let c = encrypt_chacha20_poly1305(SUNSCREEN, &key, &n, &aad);
+        assert_eq!(hex::encode(&c), concat!(
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6",
+            "3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36",
+            "92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc",
+            "3ff4def08e4b7a9de576d26586cec64b6116",
+            "1ae10b594f09e26a7e902ecbd0600691"));
+        assert_eq!(decrypt_chacha20_poly1305(&c, &key, &n, &aad).unwrap(), SUNSCREEN);
+
+        let mut forged = c.clone();
+        forged[0] ^= 1;
+        assert!(decrypt_chacha20_poly1305(&forged, &key, &n, &aad).is_none());
+        assert!(decrypt_chacha20_poly1305(&c, &key, &n, b"other aad").is_none());
+        assert!(decrypt_chacha20_poly1305(&c[..15], &key, &n, &aad).is_none());
+    }
+}
@@ -0,0 +1,331 @@
+//Версионированный контейнер для хранения шифртекста вместе с параметрами
+//Заголовок (все числа big-endian):
+//  "ENCM" | версия (1) | шифр (1 = AES-192) | режим (1 = GCM, 2 = OCB) | KDF (0 - ключ задан явно, 1 - PBKDF2-HMAC-SHA256)
+//  [для PBKDF2: итерации u32 | длина соли u8 | соль] | префикс nonce 7 байт | размер куска u32 | длина AAD u32 | AAD
+//Дальше куски: шифртекст || тэг 16 байт. Все куски, кроме последнего, ровно chunk_size байт открытого текста,
+//последний короче (возможно, пустой). nonce куска = префикс || номер куска u32 || флаг последнего куска,
+//а весь заголовок входит в AAD каждого куска - так обнаруживаются перестановка, обрезка и подмена параметров.
+//Куски - это сегменты STREAM (см. stream) с заголовком в качестве AAD
+use crate::SecretKey;
+use crate::stream::{read_full, StreamDecryptor, StreamEncryptor, StreamError, StreamReader, StreamWriter};
+use rand::Rng;
+use std::io::{self, Read, Write};
+
+pub use crate::stream::AeadMode;
+
+pub const MAGIC: [u8; 4] = *b"ENCM";
+pub const VERSION: u8 = 1;
+const CIPHER_AES192: u8 = 1;
+//ограничения, чтобы испорченный заголовок не заставил выделить гигабайты памяти
+const MAX_CHUNK_SIZE: u32 = 1 << 26;
+const MAX_AAD_LEN: u32 = 1 << 20;
+
+#[derive(Debug)]
+pub enum ContainerError {
+    Io(io::Error),
+    //не контейнер encmodes
+    BadMagic,
+    UnsupportedVersion(u8),
+    //неизвестный идентификатор шифра, режима или KDF, либо недопустимые параметры
+    UnsupportedParameters,
+    //файл обрезан: нет последнего куска или кусок неполный
+    Truncated,
+    //тэг куска не сошёлся: неверный ключ, искажённые данные или переставленные куски
+    Authentication,
+}
+
+impl From<io::Error> for ContainerError {
+    fn from(e: io::Error) -> Self {
+        ContainerError::Io(e)
+    }
+}
+
+impl From<StreamError> for ContainerError {
+    fn from(e: StreamError) -> Self {
+        match e {
+            StreamError::Io(e) => ContainerError::Io(e),
+            StreamError::Truncated => ContainerError::Truncated,
+            StreamError::Authentication => ContainerError::Authentication,
+            StreamError::CounterOverflow => ContainerError::UnsupportedParameters,
+        }
+    }
+}
+
+//параметры выработки ключа из пароля, хранятся в заголовке для расшифрования
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KdfParams {
+    None,
+    Pbkdf2HmacSha256 { iterations: u32, salt: Vec<u8> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub mode: AeadMode,
+    pub kdf: KdfParams,
+    pub nonce_prefix: [u8; 7],
+    pub chunk_size: u32,
+    pub aad: Vec<u8>,
+}
+
+impl Header {
+    //заголовок со случайным префиксом nonce и без KDF
+    pub fn new(mode: AeadMode, chunk_size: u32, aad: &[u8]) -> Self {
+        assert!((1..=MAX_CHUNK_SIZE).contains(&chunk_size), "размер куска от 1 байта до 64 МБ");
+        assert!(aad.len() <= MAX_AAD_LEN as usize, "AAD заголовка не длиннее 1 МБ");
+        let mut nonce_prefix = [0u8; 7];
+        rand::rng().fill(&mut nonce_prefix);
+        Header { mode, kdf: KdfParams::None, nonce_prefix, chunk_size, aad: aad.to_vec() }
+    }
+
+    //поля открыты, поэтому ограничения проверяются при записи: длина соли хранится в одном байте
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ContainerError> {
+        if !(1..=MAX_CHUNK_SIZE).contains(&self.chunk_size) || self.aad.len() > MAX_AAD_LEN as usize {
+            return Err(ContainerError::UnsupportedParameters);
+        }
+        let mut res = MAGIC.to_vec();
+        res.extend_from_slice(&[VERSION, CIPHER_AES192, self.mode as u8]);
+        match &self.kdf {
+            KdfParams::None => res.push(0),
+            KdfParams::Pbkdf2HmacSha256 { iterations, salt } => {
+                res.push(1);
+                let salt_len = u8::try_from(salt.len()).map_err(|_| ContainerError::UnsupportedParameters)?;
+                res.extend_from_slice(&iterations.to_be_bytes());
+                res.push(salt_len);
+                res.extend_from_slice(salt);
+            }
+        }
+        res.extend_from_slice(&self.nonce_prefix);
+        res.extend_from_slice(&self.chunk_size.to_be_bytes());
+        res.extend_from_slice(&(self.aad.len() as u32).to_be_bytes());
+        res.extend_from_slice(&self.aad);
+        Ok(res)
+    }
+
+    //чтение и разбор заголовка из начала потока
+    pub fn read_from<R: Read>(input: &mut R) -> Result<Header, ContainerError> {
+        let mut fixed = [0u8; 8];
+        read_exact(input, &mut fixed)?;
+        if fixed[..4] != MAGIC {
+            return Err(ContainerError::BadMagic);
+        }
+        if fixed[4] != VERSION {
+            return Err(ContainerError::UnsupportedVersion(fixed[4]));
+        }
+        let mode = match fixed[6] {
+            1 => AeadMode::Gcm,
+            2 => AeadMode::Ocb,
+            _ => return Err(ContainerError::UnsupportedParameters),
+        };
+        if fixed[5] != CIPHER_AES192 {
+            return Err(ContainerError::UnsupportedParameters);
+        }
+        let kdf = match fixed[7] {
+            0 => KdfParams::None,
+            1 => {
+                let mut params = [0u8; 5];
+                read_exact(input, &mut params)?;
+                let mut salt = vec![0u8; params[4] as usize];
+                read_exact(input, &mut salt)?;
+                KdfParams::Pbkdf2HmacSha256 { iterations: u32::from_be_bytes(params[..4].try_into().unwrap()), salt }
+            }
+            _ => return Err(ContainerError::UnsupportedParameters),
+        };
+        let mut rest = [0u8; 15];
+        read_exact(input, &mut rest)?;
+        let chunk_size = u32::from_be_bytes(rest[7..11].try_into().unwrap());
+        let aad_len = u32::from_be_bytes(rest[11..].try_into().unwrap());
+        if !(1..=MAX_CHUNK_SIZE).contains(&chunk_size) || aad_len > MAX_AAD_LEN {
+            return Err(ContainerError::UnsupportedParameters);
+        }
+        let mut aad = vec![0u8; aad_len as usize];
+        read_exact(input, &mut aad)?;
+        Ok(Header { mode, kdf, nonce_prefix: rest[..7].try_into().unwrap(), chunk_size, aad })
+    }
+}
+
+//read_exact, но конец потока посреди структуры - это обрезка файла
+fn read_exact<R: Read>(input: &mut R, buf: &mut [u8]) -> Result<(), ContainerError> {
+    if read_full(input, buf)? != buf.len() {
+        return Err(ContainerError::Truncated);
+    }
+    Ok(())
+}
+
+//потоковая запись контейнера: заголовок пишется сразу, куски - по мере накопления данных
+//в конце обязательно вызвать finish, иначе контейнер будет считаться обрезанным
+pub struct ContainerWriter<W: Write> {
+    inner: StreamWriter<W>,
+}
+
+impl<W: Write> ContainerWriter<W> {
+    pub fn new(mut out: W, key: &SecretKey<24>, header: Header) -> Result<Self, ContainerError> {
+        let header_bytes = header.to_bytes()?;
+        out.write_all(&header_bytes)?;
+        let encryptor = StreamEncryptor::new(key, header.mode, &header.nonce_prefix);
+        Ok(ContainerWriter { inner: StreamWriter::new(out, encryptor, header.chunk_size as usize, &header_bytes) })
+    }
+
+    //запись последнего куска, возвращает нижележащий поток
+    pub fn finish(self) -> io::Result<W> {
+        self.inner.finish()
+    }
+}
+
+impl<W: Write> Write for ContainerWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.inner.write(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+//потоковое чтение: заголовок разбирается отдельно (Header::read_from), чтобы по нему можно было получить ключ
+pub struct ContainerReader<R: Read> {
+    inner: StreamReader<R>,
+    header: Header,
+}
+
+impl<R: Read> ContainerReader<R> {
+    pub fn new(input: R, key: &SecretKey<24>, header: Header) -> Result<Self, ContainerError> {
+        let header_bytes = header.to_bytes()?;
+        let decryptor = StreamDecryptor::new(key, header.mode, &header.nonce_prefix);
+        Ok(ContainerReader {
+            inner: StreamReader::new(input, decryptor, header.chunk_size as usize, &header_bytes),
+            header,
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    //следующий расшифрованный кусок; None после последнего куска
+    pub fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, ContainerError> {
+        Ok(self.inner.next_segment()?)
+    }
+
+    pub fn read_to_end(mut self) -> Result<Vec<u8>, ContainerError> {
+        let mut res = Vec::new();
+        while let Some(chunk) = self.next_chunk()? {
+            res.extend_from_slice(&chunk);
+        }
+        Ok(res)
+    }
+}
+
+//шифрование в контейнер целиком в памяти
+pub fn encrypt_aes192_container(data: &[u8], key: &SecretKey<24>, header: Header) -> Result<Vec<u8>, ContainerError> {
+    let mut writer = ContainerWriter::new(Vec::new(), key, header)?;
+    writer.write_all(data)?;
+    Ok(writer.finish()?)
+}
+
+//расшифрование контейнера целиком, возвращает заголовок (с AAD) и открытый текст
+pub fn decrypt_aes192_container(data: &[u8], key: &SecretKey<24>) -> Result<(Header, Vec<u8>), ContainerError> {
+    let mut input = data;
+    let header = Header::read_from(&mut input)?;
+    let plaintext = ContainerReader::new(input, key, header.clone())?.read_to_end()?;
+    Ok((header, plaintext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 256) as u8).collect()
+    }
+
+    #[test]
+    fn test_container_round_trip() {
+        let key = crate::generate_key();
+        for mode in [AeadMode::Gcm, AeadMode::Ocb] {
+            for len in [0, 1, 99, 100, 101, 1000] {
+                let data = sample(len);
+                let header = Header::new(mode, 100, b"file.txt");
+                let container = encrypt_aes192_container(&data, &key, header.clone()).unwrap();
+                //на каждый кусок 16 байт тэга, последний кусок есть всегда
+                assert_eq!(container.len(), header.to_bytes().unwrap().len() + len + (len / 100 + 1) * 16);
+                let (parsed, plaintext) = decrypt_aes192_container(&container, &key).unwrap();
+                assert_eq!(parsed, header);
+                assert_eq!(plaintext, data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_container_streaming_writer() {
+        let key = crate::generate_key();
+        let data = sample(777);
+        let header = Header::new(AeadMode::Gcm, 64, b"");
+        let mut writer = ContainerWriter::new(Vec::new(), &key, header.clone()).unwrap();
+        for part in data.chunks(10) {
+            writer.write_all(part).unwrap();
+        }
+        let container = writer.finish().unwrap();
+        assert_eq!(container, {
+            let mut w = ContainerWriter::new(Vec::new(), &key, header).unwrap();
+            w.write_all(&data).unwrap();
+            w.finish().unwrap()
+        });
+        let mut input = &container[..];
+        let header = Header::read_from(&mut input).unwrap();
+        let mut reader = ContainerReader::new(input, &key, header).unwrap();
+        assert_eq!(reader.next_chunk().unwrap().unwrap(), &data[..64]);
+        assert_eq!(reader.read_to_end().unwrap(), &data[64..]);
+    }
+
+    #[test]
+    fn test_container_tampering_detected() {
+        let key = crate::generate_key();
+        let data = sample(300);
+        let header = Header::new(AeadMode::Gcm, 100, b"aad");
+        let container = encrypt_aes192_container(&data, &key, header.clone()).unwrap();
+        let header_len = header.to_bytes().unwrap().len();
+        let chunk = 100 + 16;
+
+        //обрезка по границе куска и посреди куска
+        let cut = &container[..header_len + 3 * chunk];
+        assert!(matches!(decrypt_aes192_container(cut, &key), Err(ContainerError::Truncated)));
+        let cut = &container[..container.len() - 5];
+        assert!(matches!(decrypt_aes192_container(cut, &key), Err(ContainerError::Truncated)));
+
+        //перестановка кусков
+        let mut swapped = container[..header_len].to_vec();
+        swapped.extend_from_slice(&container[header_len + chunk..header_len + 2 * chunk]);
+        swapped.extend_from_slice(&container[header_len..header_len + chunk]);
+        swapped.extend_from_slice(&container[header_len + 2 * chunk..]);
+        assert!(matches!(decrypt_aes192_container(&swapped, &key), Err(ContainerError::Authentication)));
+
+        //подмена параметров заголовка (AAD) и дописанные в конец данные
+        let mut changed = container.clone();
+        changed[header_len - 1] ^= 1;
+        assert!(matches!(decrypt_aes192_container(&changed, &key), Err(ContainerError::Authentication)));
+        let mut extended = container.clone();
+        extended.push(0);
+        assert!(matches!(decrypt_aes192_container(&extended, &key), Err(ContainerError::Authentication)));
+
+        assert!(matches!(decrypt_aes192_container(&container, &crate::generate_key()), Err(ContainerError::Authentication)));
+        let mut bad = container.clone();
+        bad[4] = 2;
+        assert!(matches!(decrypt_aes192_container(&bad, &key), Err(ContainerError::UnsupportedVersion(2))));
+        assert!(matches!(decrypt_aes192_container(b"NOT A CONTAINER", &key), Err(ContainerError::BadMagic)));
+    }
+
+    //длина соли записывается одним байтом: соль длиннее 255 байт не должна молча обрезаться
+    #[test]
+    fn test_container_salt_length_limit() {
+        let key = crate::generate_key();
+        let mut header = Header::new(AeadMode::Gcm, 100, b"");
+        header.kdf = KdfParams::Pbkdf2HmacSha256 { iterations: 1, salt: vec![0x5a; 255] };
+        let container = encrypt_aes192_container(b"data", &key, header.clone()).unwrap();
+        assert_eq!(decrypt_aes192_container(&container, &key).unwrap(), (header.clone(), b"data".to_vec()));
+
+        header.kdf = KdfParams::Pbkdf2HmacSha256 { iterations: 1, salt: vec![0x5a; 256] };
+        assert!(matches!(header.to_bytes(), Err(ContainerError::UnsupportedParameters)));
+        assert!(matches!(encrypt_aes192_container(b"data", &key, header.clone()), Err(ContainerError::UnsupportedParameters)));
+        assert!(ContainerWriter::new(Vec::new(), &key, header).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//CTR с произвольным доступом: гамму можно накладывать с любого байта без обработки начала сообщения
+//Счётчик занимает counter_len байт блока начиная с counter_offset (big-endian, как в generate_nc и SP 800-38A),
+//остальные байты начального блока - nonce. При исчерпании счётчика возвращается ошибка, а не переполнение
+use crate::{BlockCipher, SecretKey};
+use aes::Aes192;
+use cipher::KeyInit;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CtrError {
+    //позиция за пределами гаммы: счётчик заданной ширины переполнился бы
+    CounterOverflow,
+}
+
+pub struct SeekableCtr<C: BlockCipher<16>> {
+    cipher: C,
+    initial_block: [u8; 16],
+    counter_offset: usize,
+    counter_len: usize,
+    //начальное значение счётчика и сколько блоков гаммы доступно от него
+    start: u128,
+    blocks_available: u128,
+    //текущая позиция в байтах
+    pos: u64,
+}
+
+impl<C: BlockCipher<16>> SeekableCtr<C> {
+    //initial_block - первый блок счётчика; счётчик - байты counter_offset..counter_offset + counter_len
+    pub fn new(cipher: C, initial_block: [u8; 16], counter_offset: usize, counter_len: usize) -> Self {
+        assert!((1..=16).contains(&counter_len) && counter_offset + counter_len <= 16, "счётчик должен помещаться в блок");
+        let mut start = 0u128;
+        for b in &initial_block[counter_offset..counter_offset + counter_len] {
+            start = (start << 8) | *b as u128;
+        }
+        //максимальное значение счётчика - 2^(8 * counter_len) - 1
+        let max = if counter_len == 16 { u128::MAX } else { (1u128 << (8 * counter_len)) - 1 };
+        SeekableCtr {
+            cipher,
+            initial_block,
+            counter_offset,
+            counter_len,
+            start,
+            blocks_available: (max - start).saturating_add(1),
+            pos: 0,
+        }
+    }
+
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    //переход к байту byte_offset гаммы
+    pub fn seek(&mut self, byte_offset: u64) -> Result<(), CtrError> {
+        if byte_offset as u128 > self.blocks_available.saturating_mul(16) {
+            return Err(CtrError::CounterOverflow);
+        }
+        self.pos = byte_offset;
+        Ok(())
+    }
+
+    fn counter_block(&self, index: u128) -> [u8; 16] {
+        let mut block = self.initial_block;
+        let value = (self.start + index).to_be_bytes();
+        block[self.counter_offset..self.counter_offset + self.counter_len].copy_from_slice(&value[16 - self.counter_len..]);
+        block
+    }
+
+    //наложение гаммы с текущей позиции (шифрование и расшифрование совпадают), позиция сдвигается на buf.len()
+    //если гаммы не хватает, buf не изменяется
+    pub fn apply_keystream(&mut self, buf: &mut [u8]) -> Result<(), CtrError> {
+        let end = self.pos as u128 + buf.len() as u128;
+        if end.div_ceil(16) > self.blocks_available {
+            return Err(CtrError::CounterOverflow);
+        }
+        let mut done = 0;
+        while done < buf.len() {
+            let index = self.pos as u128 / 16;
+            let skip = (self.pos % 16) as usize;
+            let gamma = self.cipher.encrypt(&self.counter_block(index));
+            let n = (16 - skip).min(buf.len() - done);
+            for i in 0..n {
+                buf[done + i] ^= gamma[skip + i];
+            }
+            done += n;
+            self.pos += n as u64;
+        }
+        Ok(())
+    }
+}
+
+//объект, совместимый с encrypt_aes192_ctr/decrypt_aes192_ctr: iv || 64-битный счётчик от нуля
+pub fn aes192_seekable_ctr(key: &SecretKey<24>, iv: &[u8; 8]) -> SeekableCtr<Aes192> {
+    let mut initial_block = [0u8; 16];
+    initial_block[..8].copy_from_slice(iv);
+    SeekableCtr::new(Aes192::new(key.expose_secret().into()), initial_block, 8, 8)
+}
+
+//расшифрование произвольного диапазона байт шифртекста AES192-CTR
+pub fn decrypt_aes192_ctr_range(ciphertext_range: &[u8], key: &SecretKey<24>, iv: &[u8; 8], byte_offset: u64) -> Result<Vec<u8>, CtrError> {
+    let mut ctr = aes192_seekable_ctr(key, iv);
+    ctr.seek(byte_offset)?;
+    let mut res = ciphertext_range.to_vec();
+    ctr.apply_keystream(&mut res)?;
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::Aes128;
+
+    //F.5.1 из SP 800-38A: счётчик на весь блок и 32-битный счётчик дают одинаковую гамму в начале
+    #[test]
+    fn test_ctr_sp800_38a_vector() {
+        let key: [u8; 16] = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap().try_into().unwrap();
+        let initial: [u8; 16] = hex::decode("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff").unwrap().try_into().unwrap();
+        let p = hex::decode("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51").unwrap();
+        let expected = "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff";
+        for (offset, len) in [(0, 16), (12, 4)] {
+            let mut ctr = SeekableCtr::new(Aes128::new(&key.into()), initial, offset, len);
+            let mut buf = p.clone();
+            ctr.apply_keystream(&mut buf).unwrap();
+            assert_eq!(hex::encode(&buf), expected);
+        }
+    }
+
+    #[test]
+    fn test_seek_matches_ctr_decryption() {
+        let key = crate::generate_key();
+        let iv = crate::generate_iv_ctr();
+        let message: Vec<u8> = (0..1000u32).map(|i| (i % 253) as u8).collect();
+        let ciphertext = crate::encrypt_aes192_ctr(&message, &key, &iv);
+        for (from, to) in [(0, 1000), (5, 6), (15, 17), (16, 32), (333, 777), (999, 1000)] {
+            let part = decrypt_aes192_ctr_range(&ciphertext[from..to], &key, &iv, from as u64).unwrap();
+            assert_eq!(part, &message[from..to]);
+        }
+        //последовательные вызовы на неполных блоках продолжают гамму
+        let mut ctr = aes192_seekable_ctr(&key, &iv);
+        ctr.seek(7).unwrap();
+        let mut buf = ciphertext[7..100].to_vec();
+        let (a, b) = buf.split_at_mut(20);
+        ctr.apply_keystream(a).unwrap();
+        ctr.apply_keystream(b).unwrap();
+        assert_eq!(ctr.position(), 100);
+        assert_eq!(buf, &message[7..100]);
+    }
+
+    #[test]
+    fn test_counter_overflow_is_error() {
+        let mut initial = [0u8; 16];
+        initial[15] = 0xfe;
+        //однобайтовый счётчик от 0xfe: доступно ровно два блока
+        let mut ctr = SeekableCtr::new(Aes128::new(&[0u8; 16].into()), initial, 15, 1);
+        let mut buf = [0u8; 33];
+        assert_eq!(ctr.apply_keystream(&mut buf), Err(CtrError::CounterOverflow));
+        assert_eq!(buf, [0u8; 33]);
+        assert_eq!(ctr.position(), 0);
+        ctr.apply_keystream(&mut buf[..32]).unwrap();
+        assert_eq!(ctr.apply_keystream(&mut buf[..1]), Err(CtrError::CounterOverflow));
+        assert_eq!(ctr.seek(33), Err(CtrError::CounterOverflow));
+        ctr.seek(32).unwrap();
+    }
+}
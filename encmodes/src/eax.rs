@@ -0,0 +1,137 @@
+//Режим EAX (Bellare, Rogaway, Wagner) - OMAC с номером "домена" + CTR
+//N' = OMAC^0(N), H' = OMAC^1(aad), C = CTR_{N'}(M), тэг = N' ^ OMAC^2(C) ^ H'
+//nonce произвольной длины, длина тэга от 1 до 16 байт
+use crate::mac::omac;
+use crate::{BlockCipher, SecretKey, ct_eq};
+use aes::Aes192;
+use cipher::KeyInit;
+
+//OMAC^t(M) = OMAC([t]_16 || M)
+fn omac_t<C: BlockCipher<16>>(cipher: &C, t: u8, data: &[u8]) -> [u8; 16] {
+    let mut input = Vec::with_capacity(16 + data.len());
+    input.extend_from_slice(&[0u8; 15]);
+    input.push(t);
+    input.extend_from_slice(data);
+    omac(cipher, &input)
+}
+
+//CTR с начальным блоком N' и увеличением всего 128-битного блока
+fn eax_ctr<C: BlockCipher<16>>(cipher: &C, n: [u8; 16], data: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(data.len());
+    let mut counter = u128::from_be_bytes(n);
+    for block in data.chunks(16) {
+        let encrypt_block = cipher.encrypt(&counter.to_be_bytes());
+        res.extend(block.iter().zip(encrypt_block.iter()).map(|(a, b)| a ^ b));
+        counter = counter.wrapping_add(1);
+    }
+    res
+}
+
+fn eax_tag(n: &[u8; 16], h: &[u8; 16], c: &[u8; 16]) -> [u8; 16] {
+    let mut tag = [0u8; 16];
+    for i in 0..16 {
+        tag[i] = n[i] ^ h[i] ^ c[i];
+    }
+    tag
+}
+
+//шифрование EAX, возвращает (шифртекст, тэг длины tag_len байт)
+pub fn encrypt_eax<C: BlockCipher<16>>(
+    cipher: &C,
+    nonce: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    tag_len: usize,
+) -> (Vec<u8>, Vec<u8>) {
+    assert!((1..=16).contains(&tag_len), "длина тэга EAX должна быть от 1 до 16 байт");
+    let n = omac_t(cipher, 0, nonce);
+    let h = omac_t(cipher, 1, aad);
+    let ciphertext = eax_ctr(cipher, n, data);
+    let c = omac_t(cipher, 2, &ciphertext);
+    let tag = eax_tag(&n, &h, &c);
+    (ciphertext, tag[..tag_len].to_vec())
+}
+
+//расшифрование EAX, при несовпадении тэга возвращает None
+pub fn decrypt_eax<C: BlockCipher<16>>(
+    cipher: &C,
+    nonce: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    tag: &[u8],
+) -> Option<Vec<u8>> {
+    if !(1..=16).contains(&tag.len()) {
+        return None;
+    }
+    let n = omac_t(cipher, 0, nonce);
+    let h = omac_t(cipher, 1, aad);
+    let c = omac_t(cipher, 2, data);
+    let expected = eax_tag(&n, &h, &c);
+    if !ct_eq(&expected[..tag.len()], tag) {
+        return None;
+    }
+    Some(eax_ctr(cipher, n, data))
+}
+
+//шифрование AES192-EAX: на выходе шифртекст || тэг 16 байт
+pub fn encrypt_aes192_eax(data: &[u8], key: &SecretKey<24>, nonce: &[u8], aad: &[u8]) -> Vec<u8> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    let (mut res, tag) = encrypt_eax(&cipher, nonce, aad, data, 16);
+    res.extend_from_slice(&tag);
+    res
+}
+
+//расшифрование AES192-EAX: на входе шифртекст || тэг 16 байт
+pub fn decrypt_aes192_eax(data: &[u8], key: &SecretKey<24>, nonce: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 16 {
+        return None;
+    }
+    let cipher = Aes192::new(key.expose_secret().into());
+    let (ciphertext, tag) = data.split_at(data.len() - 16);
+    decrypt_eax(&cipher, nonce, aad, ciphertext, tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::Aes128;
+
+    fn check(key: &str, nonce: &str, aad: &str, p: &str, c: &str, t: &str) {
+        let key: [u8; 16] = hex::decode(key).unwrap().try_into().unwrap();
+        let cipher = Aes128::new(&key.into());
+        let nonce = hex::decode(nonce).unwrap();
+        let aad = hex::decode(aad).unwrap();
+        let p = hex::decode(p).unwrap();
+        let (ciphertext, tag) = encrypt_eax(&cipher, &nonce, &aad, &p, 16);
+        assert_eq!(hex::encode(&ciphertext), c);
+        assert_eq!(hex::encode(&tag), t);
+        assert_eq!(decrypt_eax(&cipher, &nonce, &aad, &ciphertext, &tag).unwrap(), p);
+    }
+
+    //тестовые векторы из статьи с описанием EAX
+    #[test]
+    fn test_eax_vectors() {
+        check("233952dee4d5ed5f9b9c6d6ff80ff478", "62ec67f9c3a4a407fcb2a8c49031a8b3", "6bfb914fd07eae6b",
+              "", "", "e037830e8389f27b025a2d6527e79d01");
+        check("91945d3f4dcbee0bf45ef52255f095a4", "becaf043b0a23d843194ba972c66debd", "fa3bfd4806eb53fa",
+              "f7fb", "19dd", "5c4c9331049d0bdab0277408f67967e5");
+        check("01f74ad64077f2e704c0f60ada3dd523", "70c3db4f0d26368400a10ed05d2bff5e", "234a3463c1264ac6",
+              "1a47cb4933", "d851d5bae0", "3a59f238a23e39199dc9266626c40f80");
+    }
+
+    #[test]
+    fn test_aes192_eax_round_trip_and_short_tag() {
+        let key = crate::generate_key();
+        let cipher = Aes192::new(key.expose_secret().into());
+        let (ciphertext, tag) = encrypt_eax(&cipher, b"nonce", b"hdr", b"payload", 8);
+        assert_eq!(tag.len(), 8);
+        assert_eq!(decrypt_eax(&cipher, b"nonce", b"hdr", &ciphertext, &tag).unwrap(), b"payload");
+        let mut broken = tag.clone();
+        broken[7] ^= 0x80;
+        assert!(decrypt_eax(&cipher, b"nonce", b"hdr", &ciphertext, &broken).is_none());
+        assert!(decrypt_eax(&cipher, b"nonc3", b"hdr", &ciphertext, &tag).is_none());
+
+        let ciphertext = encrypt_aes192_eax(b"payload", &key, b"nonce", b"");
+        assert_eq!(decrypt_aes192_eax(&ciphertext, &key, b"nonce", b"").unwrap(), b"payload");
+    }
+}
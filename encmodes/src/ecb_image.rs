@@ -0,0 +1,158 @@
+//Наглядная утечка структуры в ECB ("ECB-пингвин"): пиксели изображения шифруются разными режимами,
+//заголовок сохраняется, поэтому результат открывается обычным просмотрщиком.
+//Поддерживаются бинарный PPM (P6) и BMP без сжатия. Шифртекст обрезается до длины пикселей,
+//так что размер файла не меняется (для просмотра расшифровывать его не нужно)
+use crate::{encrypt_aes192_cbc, encrypt_aes192_cfb, encrypt_aes192_ctr, encrypt_aes192_ecb, encrypt_aes192_ofb, SecretKey};
+use crate::{generate_iv, generate_iv_ctr};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageMode {
+    Ecb,
+    Cbc,
+    Cfb,
+    Ofb,
+    Ctr,
+}
+
+impl ImageMode {
+    pub const ALL: [ImageMode; 5] = [ImageMode::Ecb, ImageMode::Cbc, ImageMode::Cfb, ImageMode::Ofb, ImageMode::Ctr];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ImageMode::Ecb => "ecb",
+            ImageMode::Cbc => "cbc",
+            ImageMode::Cfb => "cfb",
+            ImageMode::Ofb => "ofb",
+            ImageMode::Ctr => "ctr",
+        }
+    }
+}
+
+//длина заголовка PPM P6: "P6" ширина высота maxval и один пробельный символ, между полями - комментарии #
+fn ppm_header_len(data: &[u8]) -> Option<usize> {
+    let mut pos = 2;
+    for _ in 0..3 {
+        //пропуск пробелов и комментариев
+        loop {
+            match data.get(pos)? {
+                b' ' | b'\t' | b'\r' | b'\n' => pos += 1,
+                b'#' => {
+                    while *data.get(pos)? != b'\n' {
+                        pos += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+        let start = pos;
+        while data.get(pos)?.is_ascii_digit() {
+            pos += 1;
+        }
+        if pos == start {
+            return None;
+        }
+    }
+    //ровно один пробельный символ перед пикселями
+    data.get(pos)?.is_ascii_whitespace().then_some(pos + 1)
+}
+
+//длина заголовка: для BMP смещение пикселей записано в байтах 10..14
+pub fn image_header_len(data: &[u8]) -> Option<usize> {
+    if data.starts_with(b"P6") {
+        return ppm_header_len(data);
+    }
+    if data.starts_with(b"BM") && data.len() >= 14 {
+        let offset = u32::from_le_bytes(data[10..14].try_into().unwrap()) as usize;
+        return (offset <= data.len()).then_some(offset);
+    }
+    None
+}
+
+//шифрование пикселей изображения, None - если формат не распознан
+pub fn encrypt_image(image: &[u8], key: &SecretKey<24>, mode: ImageMode) -> Option<Vec<u8>> {
+    let header_len = image_header_len(image)?;
+    let (header, pixels) = image.split_at(header_len);
+    let encrypted = match mode {
+        ImageMode::Ecb => encrypt_aes192_ecb(pixels, key),
+        ImageMode::Cbc => encrypt_aes192_cbc(pixels, key, &generate_iv()),
+        ImageMode::Cfb => encrypt_aes192_cfb(pixels, key, &generate_iv()),
+        ImageMode::Ofb => encrypt_aes192_ofb(pixels, key, &generate_iv()),
+        ImageMode::Ctr => encrypt_aes192_ctr(pixels, key, &generate_iv_ctr()),
+    };
+    let mut res = header.to_vec();
+    res.extend_from_slice(&encrypted[..pixels.len()]);
+    Some(res)
+}
+
+//cargo run -- image <вход.ppm|bmp> [префикс]: пишет <префикс>_<режим>.<расширение> для всех режимов
+pub fn run_image_tool(args: &[String]) -> Result<(), String> {
+    let input = args.first().ok_or("использование: image <вход.ppm|вход.bmp> [префикс вывода]")?;
+    let image = std::fs::read(input).map_err(|e| format!("{}: {}", input, e))?;
+    let (stem, extension) = input.rsplit_once('.').unwrap_or((input, "ppm"));
+    let prefix = args.get(1).map(|s| s.as_str()).unwrap_or(stem);
+    let key = crate::generate_key();
+    for mode in ImageMode::ALL {
+        let encrypted = encrypt_image(&image, &key, mode).ok_or("поддерживаются только PPM (P6) и BMP")?;
+        let output = format!("{}_{}.{}", prefix, mode.name(), extension);
+        std::fs::write(&output, encrypted).map_err(|e| format!("{}: {}", output, e))?;
+        println!("{}", output);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbc_ecb_attacks::detect_ecb;
+
+    //"флаг" из трёх горизонтальных полос - большие одноцветные области
+    fn sample_pixels(width: usize, height: usize) -> Vec<u8> {
+        let colors = [[255u8, 255, 255], [0, 57, 166], [213, 43, 30]];
+        (0..width * height).flat_map(|i| colors[i / width * 3 / height]).collect()
+    }
+
+    fn sample_ppm() -> Vec<u8> {
+        let mut image = b"P6\n# flag\n64 48\n255\n".to_vec();
+        image.extend_from_slice(&sample_pixels(64, 48));
+        image
+    }
+
+    #[test]
+    fn test_ppm_header_preserved() {
+        let image = sample_ppm();
+        let key = crate::generate_key();
+        assert_eq!(image_header_len(&image), Some(20));
+        for mode in ImageMode::ALL {
+            let encrypted = encrypt_image(&image, &key, mode).unwrap();
+            assert_eq!(encrypted.len(), image.len());
+            assert_eq!(encrypted[..20], image[..20]);
+            assert_ne!(encrypted[20..], image[20..]);
+        }
+        assert!(encrypt_image(b"GIF89a", &key, ImageMode::Ecb).is_none());
+    }
+
+    #[test]
+    fn test_only_ecb_leaks_structure() {
+        let image = sample_ppm();
+        let key = crate::generate_key();
+        for mode in ImageMode::ALL {
+            let encrypted = encrypt_image(&image, &key, mode).unwrap();
+            //3 цвета по 16 байт: в ECB повторяется каждый блок одноцветной полосы
+            assert_eq!(detect_ecb(&encrypted[20..], 16), mode == ImageMode::Ecb);
+        }
+    }
+
+    #[test]
+    fn test_bmp_header_preserved() {
+        let pixels = sample_pixels(32, 32);
+        let mut image = b"BM".to_vec();
+        image.extend_from_slice(&((54 + pixels.len()) as u32).to_le_bytes());
+        image.extend_from_slice(&[0; 4]);
+        image.extend_from_slice(&54u32.to_le_bytes());
+        image.resize(54, 0);
+        image.extend_from_slice(&pixels);
+        let encrypted = encrypt_image(&image, &crate::generate_key(), ImageMode::Ecb).unwrap();
+        assert_eq!(encrypted[..54], image[..54]);
+        assert_eq!(encrypted.len(), image.len());
+    }
+}
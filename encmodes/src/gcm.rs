@@ -0,0 +1,265 @@
+//Режим GCM (NIST SP 800-38D) - CTR-шифрование + имитовставка GHASH в GF(2^128)
+//Работает с любым 128-битным блочным шифром через BlockCipher<16>, для AES192 есть обёртки
+use crate::{BlockCipher, SecretKey, ct_eq};
+use aes::Aes192;
+use cipher::KeyInit;
+use rand::Rng;
+
+//неприводимый многочлен x^128 + x^7 + x^2 + x + 1 в "отражённой" записи GCM
+const R: u128 = 0xE1 << 120;
+
+//генерация iv для gcm - рекомендуемая длина 12 байт
+pub fn generate_iv_gcm() -> [u8; 12] {
+    let mut iv = [0u8; 12];
+    rand::rng().fill(&mut iv);
+    iv
+}
+
+//умножение в GF(2^128) по алгоритму 1 из SP 800-38D
+//ветвления заменены масками, чтобы время не зависело от данных
+pub fn gf128_mul(x: u128, y: u128) -> u128 {
+    let mut z = 0u128;
+    let mut v = y;
+    for i in 0..128 {
+        let bit = (x >> (127 - i)) & 1;
+        z ^= v & 0u128.wrapping_sub(bit);
+        let lsb = v & 1;
+        v = (v >> 1) ^ (R & 0u128.wrapping_sub(lsb));
+    }
+    z
+}
+
+//GHASH поверх данных, дополненных нулями до целого числа блоков
+struct Ghash {
+    h: u128,
+    y: u128,
+}
+
+impl Ghash {
+    fn new(h: u128) -> Self {
+        Ghash { h, y: 0 }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for block in data.chunks(16) {
+            let mut block_array = [0u8; 16];
+            block_array[..block.len()].copy_from_slice(block);
+            self.y = gf128_mul(self.y ^ u128::from_be_bytes(block_array), self.h);
+        }
+    }
+
+    fn finalize(self) -> [u8; 16] {
+        self.y.to_be_bytes()
+    }
+}
+
+//увеличение младших 32 бит счётчика (inc32)
+fn inc32(block: [u8; 16]) -> [u8; 16] {
+    let mut res = block;
+    let counter = u32::from_be_bytes([block[12], block[13], block[14], block[15]]).wrapping_add(1);
+    res[12..].copy_from_slice(&counter.to_be_bytes());
+    res
+}
+
+//GCTR - тот же CTR, что и в encrypt_aes192_ctr, но со счётчиком inc32 и без дополнения
+fn gctr<C: BlockCipher<16>>(cipher: &C, icb: [u8; 16], data: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(data.len());
+    let mut cb = icb;
+    for block in data.chunks(16) {
+        let encrypt_block = cipher.encrypt(&cb);
+        res.extend(block.iter().zip(encrypt_block.iter()).map(|(a, b)| a ^ b));
+        cb = inc32(cb);
+    }
+    res
+}
+
+//формирование J0: для iv длины 12 байт - iv || 0^31 || 1, иначе GHASH от iv
+fn derive_j0(h: u128, iv: &[u8]) -> [u8; 16] {
+    if iv.len() == 12 {
+        let mut j0 = [0u8; 16];
+        j0[..12].copy_from_slice(iv);
+        j0[15] = 1;
+        return j0;
+    }
+    let mut ghash = Ghash::new(h);
+    ghash.update(iv);
+    let mut len_block = [0u8; 16];
+    len_block[8..].copy_from_slice(&((iv.len() as u64) * 8).to_be_bytes());
+    ghash.update(&len_block);
+    ghash.finalize()
+}
+
+//вычисление полного (16 байт) тэга по aad и шифртексту
+fn compute_tag<C: BlockCipher<16>>(cipher: &C, h: u128, j0: [u8; 16], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+    let mut ghash = Ghash::new(h);
+    ghash.update(aad);
+    ghash.update(ciphertext);
+    let mut len_block = [0u8; 16];
+    len_block[..8].copy_from_slice(&((aad.len() as u64) * 8).to_be_bytes());
+    len_block[8..].copy_from_slice(&((ciphertext.len() as u64) * 8).to_be_bytes());
+    ghash.update(&len_block);
+    let s = ghash.finalize();
+    let encrypted_j0 = cipher.encrypt(&j0);
+    let mut tag = [0u8; 16];
+    for i in 0..16 {
+        tag[i] = s[i] ^ encrypted_j0[i];
+    }
+    tag
+}
+
+//подключ хеширования H = E_K(0^128)
+fn hash_subkey<C: BlockCipher<16>>(cipher: &C) -> u128 {
+    u128::from_be_bytes(cipher.encrypt(&[0u8; 16]))
+}
+
+//шифрование GCM, возвращает (шифртекст, тэг длины tag_len байт)
+//допустимые длины тэга - от 96 до 128 бит
+pub fn encrypt_gcm<C: BlockCipher<16>>(
+    cipher: &C,
+    iv: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    tag_len: usize,
+) -> (Vec<u8>, Vec<u8>) {
+    assert!((12..=16).contains(&tag_len), "длина тэга GCM должна быть от 12 до 16 байт");
+    assert!(!iv.is_empty(), "iv для GCM не может быть пустым");
+    let h = hash_subkey(cipher);
+    let j0 = derive_j0(h, iv);
+    let ciphertext = gctr(cipher, inc32(j0), data);
+    let tag = compute_tag(cipher, h, j0, aad, &ciphertext);
+    (ciphertext, tag[..tag_len].to_vec())
+}
+
+//расшифрование GCM, при несовпадении тэга возвращает None и открытый текст не раскрывается
+pub fn decrypt_gcm<C: BlockCipher<16>>(
+    cipher: &C,
+    iv: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    tag: &[u8],
+) -> Option<Vec<u8>> {
+    if !(12..=16).contains(&tag.len()) || iv.is_empty() {
+        return None;
+    }
+    let h = hash_subkey(cipher);
+    let j0 = derive_j0(h, iv);
+    let expected = compute_tag(cipher, h, j0, aad, data);
+    if !ct_eq(&expected[..tag.len()], tag) {
+        return None;
+    }
+    Some(gctr(cipher, inc32(j0), data))
+}
+
+//шифрование AES192-GCM: на выходе шифртекст || тэг 16 байт
+pub fn encrypt_aes192_gcm(data: &[u8], key: &SecretKey<24>, iv: &[u8], aad: &[u8]) -> Vec<u8> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    let (mut res, tag) = encrypt_gcm(&cipher, iv, aad, data, 16);
+    res.extend_from_slice(&tag);
+    res
+}
+
+//расшифрование AES192-GCM: на входе шифртекст || тэг 16 байт
+pub fn decrypt_aes192_gcm(data: &[u8], key: &SecretKey<24>, iv: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 16 {
+        return None;
+    }
+    let cipher = Aes192::new(key.expose_secret().into());
+    let (ciphertext, tag) = data.split_at(data.len() - 16);
+    decrypt_gcm(&cipher, iv, aad, ciphertext, tag)
+}
+
+//тестовые векторы из спецификации GCM (McGrew, Viega), которые приводит NIST
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::Aes128;
+
+    const P: &str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255";
+    const A: &str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+    const IV: &str = "cafebabefacedbaddecaf888";
+    const IV_LONG: &str = "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b";
+
+    fn check<C: BlockCipher<16>>(cipher: &C, iv: &str, aad: &str, p: &str, c: &str, t: &str) {
+        let iv = hex::decode(iv).unwrap();
+        let aad = hex::decode(aad).unwrap();
+        let p = hex::decode(p).unwrap();
+        let (ciphertext, tag) = encrypt_gcm(cipher, &iv, &aad, &p, 16);
+        assert_eq!(hex::encode(&ciphertext), c);
+        assert_eq!(hex::encode(&tag), t);
+        let plaintext = decrypt_gcm(cipher, &iv, &aad, &ciphertext, &tag).expect("тэг должен сойтись");
+        assert_eq!(plaintext, p);
+    }
+
+    #[test]
+    fn test_gcm_aes128_vectors() {
+        let zero = Aes128::new(&[0u8; 16].into());
+        check(&zero, "000000000000000000000000", "", "", "", "58e2fccefa7e3061367f1d57a4e7455a");
+        check(&zero, "000000000000000000000000", "", "00000000000000000000000000000000",
+              "0388dace60b6a392f328c2b971b2fe78", "ab6e47d42cec13bdf53a67b21257bddf");
+
+        let key: [u8; 16] = hex::decode("feffe9928665731c6d6a8f9467308308").unwrap().try_into().unwrap();
+        let cipher = Aes128::new(&key.into());
+        check(&cipher, IV, "", P,
+              "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985",
+              "4d5c2af327cd64a62cf35abd2ba6fab4");
+        check(&cipher, IV, A, &P[..120],
+              "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+              "5bc94fbc3221a5db94fae95ae7121a47");
+        //iv длины 8 и 60 байт - J0 через GHASH
+        check(&cipher, "cafebabefacedbad", A, &P[..120],
+              "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c742373806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f4598",
+              "3612d2e79e3b0785561be14aaca2fccb");
+        check(&cipher, IV_LONG, A, &P[..120],
+              "8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca701e4a9a4fba43c90ccdcb281d48c7c6fd62875d2aca417034c34aee5",
+              "619cc5aefffe0bfa462af43c1699d050");
+    }
+
+    #[test]
+    fn test_gcm_aes192_vectors() {
+        let zero = Aes192::new(&[0u8; 24].into());
+        check(&zero, "000000000000000000000000", "", "", "", "cd33b28ac773f74ba00ed1f312572435");
+        check(&zero, "000000000000000000000000", "", "00000000000000000000000000000000",
+              "98e7247c07f0fe411c267e4384b0f600", "2ff58d80033927ab8ef4d4587514f0fb");
+
+        let key: [u8; 24] = hex::decode("feffe9928665731c6d6a8f9467308308feffe9928665731c").unwrap().try_into().unwrap();
+        let cipher = Aes192::new(&key.into());
+        check(&cipher, IV, "", P,
+              "3980ca0b3c00e841eb06fac4872a2757859e1ceaa6efd984628593b40ca1e19c7d773d00c144c525ac619d18c84a3f4718e2448b2fe324d9ccda2710acade256",
+              "9924a7c8587336bfb118024db8674a14");
+        check(&cipher, IV, A, &P[..120],
+              "3980ca0b3c00e841eb06fac4872a2757859e1ceaa6efd984628593b40ca1e19c7d773d00c144c525ac619d18c84a3f4718e2448b2fe324d9ccda2710",
+              "2519498e80f1478f37ba55bd6d27618c");
+        check(&cipher, "cafebabefacedbad", A, &P[..120],
+              "0f10f599ae14a154ed24b36e25324db8c566632ef2bbb34f8347280fc4507057fddc29df9a471f75c66541d4d4dad1c9e93a19a58e8b473fa0f062f7",
+              "65dcc57fcf623a24094fcca40d3533f8");
+        check(&cipher, IV_LONG, A, &P[..120],
+              "d27e88681ce3243c4830165a8fdcf9ff1de9a1d8e6b447ef6ef7b79828666e4581e79012af34ddd9e2f037589b292db3e67c036745fa22e7e9b7373b",
+              "dcf566ff291c25bbb8568fc3d376a6d9");
+    }
+
+    #[test]
+    fn test_gcm_truncated_tag_and_tamper() {
+        let key = crate::generate_key();
+        let cipher = Aes192::new(key.expose_secret().into());
+        let iv = generate_iv_gcm();
+        let (ciphertext, tag) = encrypt_gcm(&cipher, &iv, b"header", b"secret message", 12);
+        assert_eq!(tag.len(), 12);
+        assert!(decrypt_gcm(&cipher, &iv, b"header", &ciphertext, &tag).is_some());
+        assert!(decrypt_gcm(&cipher, &iv, b"Header", &ciphertext, &tag).is_none());
+        let mut broken = ciphertext.clone();
+        broken[0] ^= 1;
+        assert!(decrypt_gcm(&cipher, &iv, b"header", &broken, &tag).is_none());
+        assert!(decrypt_gcm(&cipher, &iv, b"header", &ciphertext, &tag[..11]).is_none());
+    }
+
+    #[test]
+    fn test_aes192_gcm_round_trip() {
+        let key = crate::generate_key();
+        let iv = generate_iv_gcm();
+        let message = "Стояла зима.".as_bytes();
+        let ciphertext = encrypt_aes192_gcm(message, &key, &iv, b"aad");
+        assert_eq!(ciphertext.len(), message.len() + 16);
+        assert_eq!(decrypt_aes192_gcm(&ciphertext, &key, &iv, b"aad").unwrap(), message);
+        assert!(decrypt_aes192_gcm(&ciphertext, &key, &iv, b"").is_none());
+    }
+}
@@ -0,0 +1,191 @@
+//Режим AES-GCM-SIV (RFC 8452) - аналог GCM, устойчивый к повтору nonce
+//Для каждого nonce из ключа порождаются ключ аутентификации и ключ шифрования,
+//тэг = E(POLYVAL(aad, P, длины) ^ nonce) служит начальным счётчиком CTR
+use crate::gcm::gf128_mul;
+use crate::{BlockCipher, SecretKey, ct_eq};
+use aes::{Aes128, Aes256};
+use cipher::KeyInit;
+use zeroize::{Zeroize, Zeroizing};
+
+//POLYVAL выражается через умножение GHASH: H' = mulX_GHASH(ByteReverse(H)), блоки переворачиваются
+struct Polyval {
+    h: u128,
+    s: u128,
+}
+
+impl Polyval {
+    fn new(key: &[u8; 16]) -> Self {
+        let h = u128::from_le_bytes(*key);
+        //умножение на x в "отражённом" представлении GHASH
+        let h = (h >> 1) ^ ((0xE1 << 120) & 0u128.wrapping_sub(h & 1));
+        Polyval { h, s: 0 }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for block in data.chunks(16) {
+            let mut block_array = [0u8; 16];
+            block_array[..block.len()].copy_from_slice(block);
+            self.s = gf128_mul(self.s ^ u128::from_le_bytes(block_array), self.h);
+        }
+    }
+
+    fn finalize(self) -> [u8; 16] {
+        self.s.to_le_bytes()
+    }
+}
+
+//h - ключ аутентификации сообщения
+impl Drop for Polyval {
+    fn drop(&mut self) {
+        self.h.zeroize();
+        self.s.zeroize();
+    }
+}
+
+//ключи сообщения: первые 8 байт E_K(le32(i) || nonce) для i = 0, 1, ...; затираются после использования
+fn derive_keys<C: BlockCipher<16>>(kgk: &C, nonce: &[u8; 12], enc_key_len: usize) -> (Zeroizing<[u8; 16]>, Zeroizing<Vec<u8>>) {
+    let mut material = Zeroizing::new(Vec::with_capacity(16 + enc_key_len));
+    for i in 0..(16 + enc_key_len) / 8 {
+        let mut block = [0u8; 16];
+        block[..4].copy_from_slice(&(i as u32).to_le_bytes());
+        block[4..].copy_from_slice(nonce);
+        material.extend_from_slice(&Zeroizing::new(kgk.encrypt(&block))[..8]);
+    }
+    let auth_key = Zeroizing::new(material[..16].try_into().unwrap());
+    (auth_key, Zeroizing::new(material[16..].to_vec()))
+}
+
+fn compute_tag<C: BlockCipher<16>>(enc: &C, auth_key: &[u8; 16], nonce: &[u8; 12], aad: &[u8], data: &[u8]) -> [u8; 16] {
+    let mut polyval = Polyval::new(auth_key);
+    polyval.update(aad);
+    polyval.update(data);
+    let mut len_block = [0u8; 16];
+    len_block[..8].copy_from_slice(&((aad.len() as u64) * 8).to_le_bytes());
+    len_block[8..].copy_from_slice(&((data.len() as u64) * 8).to_le_bytes());
+    polyval.update(&len_block);
+    let mut s = polyval.finalize();
+    for i in 0..12 {
+        s[i] ^= nonce[i];
+    }
+    s[15] &= 0x7f;
+    enc.encrypt(&s)
+}
+
+//CTR с 32-битным счётчиком little-endian в первых 4 байтах, начальный блок - тэг со старшим битом 1
+fn gcm_siv_ctr<C: BlockCipher<16>>(enc: &C, tag: &[u8; 16], data: &[u8]) -> Vec<u8> {
+    let mut block = *tag;
+    block[15] |= 0x80;
+    let mut res = Vec::with_capacity(data.len());
+    for chunk in data.chunks(16) {
+        let encrypt_block = enc.encrypt(&block);
+        res.extend(chunk.iter().zip(encrypt_block.iter()).map(|(a, b)| a ^ b));
+        let counter = u32::from_le_bytes(block[..4].try_into().unwrap()).wrapping_add(1);
+        block[..4].copy_from_slice(&counter.to_le_bytes());
+    }
+    res
+}
+
+fn seal<C: BlockCipher<16>>(enc: &C, auth_key: &[u8; 16], nonce: &[u8; 12], aad: &[u8], data: &[u8]) -> Vec<u8> {
+    let tag = compute_tag(enc, auth_key, nonce, aad, data);
+    let mut res = gcm_siv_ctr(enc, &tag, data);
+    res.extend_from_slice(&tag);
+    res
+}
+
+fn open<C: BlockCipher<16>>(enc: &C, auth_key: &[u8; 16], nonce: &[u8; 12], aad: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    let (ciphertext, tag) = data.split_at(data.len() - 16);
+    let tag: [u8; 16] = tag.try_into().unwrap();
+    let plaintext = gcm_siv_ctr(enc, &tag, ciphertext);
+    let expected = compute_tag(enc, auth_key, nonce, aad, &plaintext);
+    if !ct_eq(&expected, &tag) {
+        return None;
+    }
+    Some(plaintext)
+}
+
+//шифрование и расшифрование на ключе-генераторе C (AES-128 или AES-256): ключ шифрования той же длины
+fn encrypt_with<C: BlockCipher<16> + KeyInit>(data: &[u8], key: &[u8], nonce: &[u8; 12], aad: &[u8]) -> Vec<u8> {
+    let (auth_key, enc_key) = derive_keys(&C::new_from_slice(key).unwrap(), nonce, key.len());
+    seal(&C::new_from_slice(&enc_key).unwrap(), &auth_key, nonce, aad, data)
+}
+
+fn decrypt_with<C: BlockCipher<16> + KeyInit>(data: &[u8], key: &[u8], nonce: &[u8; 12], aad: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 16 {
+        return None;
+    }
+    let (auth_key, enc_key) = derive_keys(&C::new_from_slice(key).unwrap(), nonce, key.len());
+    open(&C::new_from_slice(&enc_key).unwrap(), &auth_key, nonce, aad, data)
+}
+
+//шифрование AES-128-GCM-SIV; на выходе шифртекст || тэг 16 байт
+pub fn encrypt_aes128_gcm_siv(data: &[u8], key: &SecretKey<16>, nonce: &[u8; 12], aad: &[u8]) -> Vec<u8> {
+    encrypt_with::<Aes128>(data, key.expose_secret(), nonce, aad)
+}
+
+//расшифрование AES-128-GCM-SIV, при несовпадении тэга возвращает None
+pub fn decrypt_aes128_gcm_siv(data: &[u8], key: &SecretKey<16>, nonce: &[u8; 12], aad: &[u8]) -> Option<Vec<u8>> {
+    decrypt_with::<Aes128>(data, key.expose_secret(), nonce, aad)
+}
+
+pub fn encrypt_aes256_gcm_siv(data: &[u8], key: &SecretKey<32>, nonce: &[u8; 12], aad: &[u8]) -> Vec<u8> {
+    encrypt_with::<Aes256>(data, key.expose_secret(), nonce, aad)
+}
+
+pub fn decrypt_aes256_gcm_siv(data: &[u8], key: &SecretKey<32>, nonce: &[u8; 12], aad: &[u8]) -> Option<Vec<u8>> {
+    decrypt_with::<Aes256>(data, key.expose_secret(), nonce, aad)
+}
+
+//векторы из приложения C RFC 8452
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONCE: &str = "030000000000000000000000";
+
+    fn check128(aad: &str, p: &str, result: &str) {
+        let key = SecretKey::from_slice(&hex::decode("01000000000000000000000000000000").unwrap()).unwrap();
+        let nonce: [u8; 12] = hex::decode(NONCE).unwrap().try_into().unwrap();
+        let (aad, p) = (hex::decode(aad).unwrap(), hex::decode(p).unwrap());
+        let c = encrypt_aes128_gcm_siv(&p, &key, &nonce, &aad);
+        assert_eq!(hex::encode(&c), result);
+        assert_eq!(decrypt_aes128_gcm_siv(&c, &key, &nonce, &aad).unwrap(), p);
+    }
+
+    fn check256(aad: &str, p: &str, result: &str) {
+        let key = SecretKey::from_slice(&hex::decode("0100000000000000000000000000000000000000000000000000000000000000").unwrap()).unwrap();
+        let nonce: [u8; 12] = hex::decode(NONCE).unwrap().try_into().unwrap();
+        let (aad, p) = (hex::decode(aad).unwrap(), hex::decode(p).unwrap());
+        let c = encrypt_aes256_gcm_siv(&p, &key, &nonce, &aad);
+        assert_eq!(hex::encode(&c), result);
+        assert_eq!(decrypt_aes256_gcm_siv(&c, &key, &nonce, &aad).unwrap(), p);
+    }
+
+    #[test]
+    fn test_aes128_gcm_siv_vectors() {
+        check128("", "", "dc20e2d83f25705bb49e439eca56de25");
+        check128("", "0100000000000000", "b5d839330ac7b786578782fff6013b815b287c22493a364c");
+        check128("", "010000000000000000000000", "7323ea61d05932260047d942a4978db357391a0bc4fdec8b0d106639");
+        check128("", "01000000000000000000000000000000", "743f7c8077ab25f8624e2e948579cf77303aaf90f6fe21199c6068577437a0c4");
+        //непустые AAD и открытый текст: POLYVAL проходит и по AAD
+        check128("01", "0200000000000000", "1e6daba35669f4273b0a1a2560969cdf790d99759abd1508");
+    }
+
+    #[test]
+    fn test_aes256_gcm_siv_vectors() {
+        check256("", "", "07f5f4169bbf55a8400cd47ea6fd400f");
+        check256("", "0100000000000000", "c2ef328e5c71c83b843122130f7364b761e0b97427e3df28");
+        check256("01", "0200000000000000", "1de22967237a813291213f267e3b452f02d01ae33e4ec854");
+    }
+
+    #[test]
+    fn test_gcm_siv_nonce_reuse_leaks_only_equality() {
+        let key = SecretKey::from([0x5au8; 32]);
+        let nonce = [9u8; 12];
+        let c1 = encrypt_aes256_gcm_siv(b"message one", &key, &nonce, b"aad");
+        let c2 = encrypt_aes256_gcm_siv(b"message two", &key, &nonce, b"aad");
+        //в отличие от GCM при повторе nonce гаммы разные
+        assert_ne!(c1[..4], c2[..4]);
+        assert_eq!(c1, encrypt_aes256_gcm_siv(b"message one", &key, &nonce, b"aad"));
+        assert!(decrypt_aes256_gcm_siv(&c1, &key, &nonce, b"AAD").is_none());
+    }
+}
@@ -0,0 +1,257 @@
+//Блочные шифры ГОСТ Р 34.12-2015: "Кузнечик" (блок 128 бит) и "Магма" (блок 64 бита), ключ 256 бит
+//Нужны для режимов из российских рекомендаций (MGM, ACPKM), где тестовые векторы даны именно для них
+use crate::BlockCipher;
+use zeroize::Zeroize;
+
+//нелинейная подстановка pi "Кузнечика"
+const PI: [u8; 256] = [
+    252, 238, 221, 17, 207, 110, 49, 22, 251, 196, 250, 218, 35, 197, 4, 77,
+    233, 119, 240, 219, 147, 46, 153, 186, 23, 54, 241, 187, 20, 205, 95, 193,
+    249, 24, 101, 90, 226, 92, 239, 33, 129, 28, 60, 66, 139, 1, 142, 79,
+    5, 132, 2, 174, 227, 106, 143, 160, 6, 11, 237, 152, 127, 212, 211, 31,
+    235, 52, 44, 81, 234, 200, 72, 171, 242, 42, 104, 162, 253, 58, 206, 204,
+    181, 112, 14, 86, 8, 12, 118, 18, 191, 114, 19, 71, 156, 183, 93, 135,
+    21, 161, 150, 41, 16, 123, 154, 199, 243, 145, 120, 111, 157, 158, 178, 177,
+    50, 117, 25, 61, 255, 53, 138, 126, 109, 84, 198, 128, 195, 189, 13, 87,
+    223, 245, 36, 169, 62, 168, 67, 201, 215, 121, 214, 246, 124, 34, 185, 3,
+    224, 15, 236, 222, 122, 148, 176, 188, 220, 232, 40, 80, 78, 51, 10, 74,
+    167, 151, 96, 115, 30, 0, 98, 68, 26, 184, 56, 130, 100, 159, 38, 65,
+    173, 69, 70, 146, 39, 94, 85, 47, 140, 163, 165, 125, 105, 213, 149, 59,
+    7, 88, 179, 64, 134, 172, 29, 247, 48, 55, 107, 228, 136, 217, 231, 137,
+    225, 27, 131, 73, 76, 63, 248, 254, 141, 83, 170, 144, 202, 216, 133, 97,
+    32, 113, 103, 164, 45, 43, 9, 91, 203, 155, 37, 208, 190, 229, 108, 82,
+    89, 166, 116, 210, 230, 244, 180, 192, 209, 102, 175, 194, 57, 75, 99, 182,
+];
+
+//обратная подстановка, считается при компиляции
+const PI_INV: [u8; 256] = {
+    let mut inv = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        inv[PI[i] as usize] = i as u8;
+        i += 1;
+    }
+    inv
+};
+
+//коэффициенты линейного преобразования l
+const L_COEFS: [u8; 16] = [148, 32, 133, 16, 194, 192, 1, 251, 1, 192, 194, 16, 133, 32, 148, 1];
+
+//умножение в GF(2^8) по модулю x^8 + x^7 + x^6 + x + 1
+fn gf256_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut res = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            res ^= a;
+        }
+        let hi = a & 0x80;
+        a <<= 1;
+        if hi != 0 {
+            a ^= 0xC3;
+        }
+        b >>= 1;
+    }
+    res
+}
+
+fn kuz_l_func(block: &[u8; 16]) -> u8 {
+    let mut res = 0u8;
+    for i in 0..16 {
+        res ^= gf256_mul(block[i], L_COEFS[i]);
+    }
+    res
+}
+
+//преобразование L = R^16, где R - сдвиг на байт с записью l(a) в старший байт
+fn kuz_l(block: &[u8; 16]) -> [u8; 16] {
+    let mut b = *block;
+    for _ in 0..16 {
+        let l = kuz_l_func(&b);
+        b.copy_within(0..15, 1);
+        b[0] = l;
+    }
+    b
+}
+
+fn kuz_l_inv(block: &[u8; 16]) -> [u8; 16] {
+    let mut b = *block;
+    for _ in 0..16 {
+        let a0 = b[0];
+        b.copy_within(1..16, 0);
+        b[15] = a0;
+        b[15] = kuz_l_func(&b);
+    }
+    b
+}
+
+fn kuz_s(block: &[u8; 16]) -> [u8; 16] {
+    block.map(|x| PI[x as usize])
+}
+
+fn kuz_s_inv(block: &[u8; 16]) -> [u8; 16] {
+    block.map(|x| PI_INV[x as usize])
+}
+
+fn kuz_x(a: &[u8; 16], b: &[u8; 16]) -> [u8; 16] {
+    let mut res = [0u8; 16];
+    for i in 0..16 {
+        res[i] = a[i] ^ b[i];
+    }
+    res
+}
+
+pub struct Kuznyechik {
+    round_keys: [[u8; 16]; 10],
+}
+
+impl Kuznyechik {
+    pub fn new(key: &[u8; 32]) -> Self {
+        let mut round_keys = [[0u8; 16]; 10];
+        let mut k1: [u8; 16] = key[..16].try_into().unwrap();
+        let mut k2: [u8; 16] = key[16..].try_into().unwrap();
+        round_keys[0] = k1;
+        round_keys[1] = k2;
+        //итерационные константы C_i = L(Vec128(i)) и 8 раундов сети Фейстеля на каждую пару ключей
+        for i in 0..4 {
+            for j in 0..8 {
+                let mut c = [0u8; 16];
+                c[15] = (8 * i + j + 1) as u8;
+                let c = kuz_l(&c);
+                let f = kuz_x(&kuz_l(&kuz_s(&kuz_x(&k1, &c))), &k2);
+                k2 = k1;
+                k1 = f;
+            }
+            round_keys[2 * i + 2] = k1;
+            round_keys[2 * i + 3] = k2;
+        }
+        k1.zeroize();
+        k2.zeroize();
+        Kuznyechik { round_keys }
+    }
+}
+
+//раундовые ключи затираются вместе с шифром
+impl Drop for Kuznyechik {
+    fn drop(&mut self) {
+        self.round_keys.zeroize();
+    }
+}
+
+impl BlockCipher<16> for Kuznyechik {
+    fn encrypt(&self, block: &[u8; 16]) -> [u8; 16] {
+        let mut b = *block;
+        for k in &self.round_keys[..9] {
+            b = kuz_l(&kuz_s(&kuz_x(&b, k)));
+        }
+        kuz_x(&b, &self.round_keys[9])
+    }
+
+    fn decrypt(&self, block: &[u8; 16]) -> [u8; 16] {
+        let mut b = kuz_x(block, &self.round_keys[9]);
+        for k in self.round_keys[..9].iter().rev() {
+            b = kuz_x(&kuz_s_inv(&kuz_l_inv(&b)), k);
+        }
+        b
+    }
+}
+
+//подстановки "Магмы" (параметры id-tc26-gost-28147-param-Z), PI_MAGMA[i] применяется к i-й тетраде
+const PI_MAGMA: [[u8; 16]; 8] = [
+    [12, 4, 6, 2, 10, 5, 11, 9, 14, 8, 13, 7, 0, 3, 15, 1],
+    [6, 8, 2, 3, 9, 10, 5, 12, 1, 14, 4, 7, 11, 13, 0, 15],
+    [11, 3, 5, 8, 2, 15, 10, 13, 14, 1, 7, 4, 12, 9, 6, 0],
+    [12, 8, 2, 1, 13, 4, 15, 6, 7, 0, 10, 5, 3, 14, 9, 11],
+    [7, 15, 5, 10, 8, 1, 6, 13, 0, 9, 3, 14, 11, 4, 2, 12],
+    [5, 13, 15, 6, 9, 2, 12, 10, 11, 7, 8, 1, 4, 3, 14, 0],
+    [8, 14, 2, 5, 6, 9, 1, 12, 15, 4, 11, 0, 13, 10, 3, 7],
+    [1, 7, 14, 13, 0, 5, 8, 3, 4, 15, 10, 6, 9, 12, 11, 2],
+];
+
+//g[k](a) = t(a + k mod 2^32) <<< 11
+fn magma_g(k: u32, a: u32) -> u32 {
+    let x = a.wrapping_add(k);
+    let mut t = 0u32;
+    for (i, pi) in PI_MAGMA.iter().enumerate() {
+        let nibble = (x >> (4 * i)) & 0xF;
+        t |= (pi[nibble as usize] as u32) << (4 * i);
+    }
+    t.rotate_left(11)
+}
+
+pub struct Magma {
+    keys: [u32; 8],
+}
+
+impl Magma {
+    pub fn new(key: &[u8; 32]) -> Self {
+        let mut keys = [0u32; 8];
+        for (i, chunk) in key.chunks(4).enumerate() {
+            keys[i] = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Magma { keys }
+    }
+
+    //порядок раундовых ключей: K1..K8 три раза, затем K8..K1
+    fn round_key(&self, round: usize) -> u32 {
+        if round < 24 { self.keys[round % 8] } else { self.keys[7 - round % 8] }
+    }
+
+    fn rounds(&self, block: &[u8; 8], order: impl Iterator<Item = usize>) -> [u8; 8] {
+        let mut a1 = u32::from_be_bytes(block[..4].try_into().unwrap());
+        let mut a0 = u32::from_be_bytes(block[4..].try_into().unwrap());
+        for round in order {
+            let t = a1 ^ magma_g(self.round_key(round), a0);
+            a1 = a0;
+            a0 = t;
+        }
+        //в последнем раунде (G*) половины не переставляются
+        let mut res = [0u8; 8];
+        res[..4].copy_from_slice(&a0.to_be_bytes());
+        res[4..].copy_from_slice(&a1.to_be_bytes());
+        res
+    }
+}
+
+impl Drop for Magma {
+    fn drop(&mut self) {
+        self.keys.zeroize();
+    }
+}
+
+impl BlockCipher<8> for Magma {
+    fn encrypt(&self, block: &[u8; 8]) -> [u8; 8] {
+        self.rounds(block, 0..32)
+    }
+
+    fn decrypt(&self, block: &[u8; 8]) -> [u8; 8] {
+        self.rounds(block, (0..32).rev())
+    }
+}
+
+//контрольные примеры из ГОСТ Р 34.12-2015
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kuznyechik_vector() {
+        let key: [u8; 32] = hex::decode("8899aabbccddeeff0011223344556677fedcba98765432100123456789abcdef")
+            .unwrap().try_into().unwrap();
+        let cipher = Kuznyechik::new(&key);
+        let pt: [u8; 16] = hex::decode("1122334455667700ffeeddccbbaa9988").unwrap().try_into().unwrap();
+        let ct = cipher.encrypt(&pt);
+        assert_eq!(hex::encode(ct), "7f679d90bebc24305a468d42b9d4edcd");
+        assert_eq!(cipher.decrypt(&ct), pt);
+    }
+
+    #[test]
+    fn test_magma_vector() {
+        let key: [u8; 32] = hex::decode("ffeeddccbbaa99887766554433221100f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff")
+            .unwrap().try_into().unwrap();
+        let cipher = Magma::new(&key);
+        let pt: [u8; 8] = hex::decode("fedcba9876543210").unwrap().try_into().unwrap();
+        let ct = cipher.encrypt(&pt);
+        assert_eq!(hex::encode(ct), "4ee901e5c2d8ca3d");
+        assert_eq!(cipher.decrypt(&ct), pt);
+    }
+}
@@ -0,0 +1,226 @@
+//Обёртывание ключей AES Key Wrap (RFC 3394, KW) и Key Wrap with Padding (RFC 5649, KWP), NIST SP 800-38F
+//Шифруемый ключ делится на 64-битные полублоки, которые 6 раз перемешиваются через блочный шифр;
+//целостность проверяется по начальному значению (ICV) в первом полублоке
+use crate::{BlockCipher, SecretBytes, SecretKey, ct_eq};
+use aes::Aes192;
+use cipher::KeyInit;
+use zeroize::Zeroizing;
+
+//ICV по умолчанию для KW
+const KW_ICV: [u8; 8] = [0xA6; 8];
+//первые 4 байта альтернативного ICV для KWP, за ними длина ключа в байтах
+const KWP_ICV: [u8; 4] = [0xA6, 0x59, 0x59, 0xA6];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyWrapError {
+    //длина ключа (при обёртывании) или обёрнутого ключа не подходит для режима
+    InvalidLength,
+    //ICV (или дополнение в KWP) не совпал - неверный ключ обёртывания или искажённые данные
+    IntegrityCheckFailed,
+}
+
+//функция W: a - начальный полублок, r - полублоки ключа
+fn wrap_core<C: BlockCipher<16>>(cipher: &C, a: [u8; 8], r: &mut [[u8; 8]]) -> [u8; 8] {
+    let n = r.len();
+    let mut a = u64::from_be_bytes(a);
+    for j in 0..6 {
+        for (i, ri) in r.iter_mut().enumerate() {
+            let mut b = [0u8; 16];
+            b[..8].copy_from_slice(&a.to_be_bytes());
+            b[8..].copy_from_slice(ri);
+            let b = cipher.encrypt(&b);
+            let t = (n * j + i + 1) as u64;
+            a = u64::from_be_bytes(b[..8].try_into().unwrap()) ^ t;
+            ri.copy_from_slice(&b[8..]);
+        }
+    }
+    a.to_be_bytes()
+}
+
+//обратная функция W^-1, возвращает восстановленный начальный полублок
+fn unwrap_core<C: BlockCipher<16>>(cipher: &C, a: [u8; 8], r: &mut [[u8; 8]]) -> [u8; 8] {
+    let n = r.len();
+    let mut a = u64::from_be_bytes(a);
+    for j in (0..6).rev() {
+        for i in (0..n).rev() {
+            let t = (n * j + i + 1) as u64;
+            let mut b = [0u8; 16];
+            b[..8].copy_from_slice(&(a ^ t).to_be_bytes());
+            b[8..].copy_from_slice(&r[i]);
+            let b = cipher.decrypt(&b);
+            a = u64::from_be_bytes(b[..8].try_into().unwrap());
+            r[i].copy_from_slice(&b[8..]);
+        }
+    }
+    a.to_be_bytes()
+}
+
+fn to_semiblocks(data: &[u8]) -> Vec<[u8; 8]> {
+    data.chunks(8).map(|c| c.try_into().unwrap()).collect()
+}
+
+fn join(a: &[u8; 8], r: &[[u8; 8]]) -> Vec<u8> {
+    let mut res = a.to_vec();
+    for block in r {
+        res.extend_from_slice(block);
+    }
+    res
+}
+
+//KW: ключ - не меньше двух полублоков, длина кратна 8 байтам
+pub fn wrap_kw<C: BlockCipher<16>>(cipher: &C, key_data: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    if key_data.len() < 16 || !key_data.len().is_multiple_of(8) {
+        return Err(KeyWrapError::InvalidLength);
+    }
+    let mut r = to_semiblocks(key_data);
+    let a = wrap_core(cipher, KW_ICV, &mut r);
+    Ok(join(&a, &r))
+}
+
+//развёрнутый ключ возвращается как SecretBytes; промежуточные копии затираются, в том числе при ошибке ICV
+pub fn unwrap_kw<C: BlockCipher<16>>(cipher: &C, wrapped: &[u8]) -> Result<SecretBytes, KeyWrapError> {
+    if wrapped.len() < 24 || !wrapped.len().is_multiple_of(8) {
+        return Err(KeyWrapError::InvalidLength);
+    }
+    let mut r = Zeroizing::new(to_semiblocks(&wrapped[8..]));
+    let a = unwrap_core(cipher, wrapped[..8].try_into().unwrap(), &mut r);
+    if !ct_eq(&a, &KW_ICV) {
+        return Err(KeyWrapError::IntegrityCheckFailed);
+    }
+    Ok(SecretBytes::from(r.concat()))
+}
+
+//KWP: ключ любой ненулевой длины, дополняется нулями до кратной 8
+pub fn wrap_kwp<C: BlockCipher<16>>(cipher: &C, key_data: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    //длина ключа записывается в 32 бита ICV
+    if key_data.is_empty() || key_data.len() > u32::MAX as usize {
+        return Err(KeyWrapError::InvalidLength);
+    }
+    let mut aiv = [0u8; 8];
+    aiv[..4].copy_from_slice(&KWP_ICV);
+    aiv[4..].copy_from_slice(&(key_data.len() as u32).to_be_bytes());
+    let mut padded = Zeroizing::new(key_data.to_vec());
+    padded.resize(key_data.len().div_ceil(8) * 8, 0);
+    if padded.len() == 8 {
+        //один полублок шифруется одним вызовом шифра
+        let mut b = [0u8; 16];
+        b[..8].copy_from_slice(&aiv);
+        b[8..].copy_from_slice(&padded);
+        return Ok(cipher.encrypt(&b).to_vec());
+    }
+    let mut r = to_semiblocks(&padded);
+    let a = wrap_core(cipher, aiv, &mut r);
+    Ok(join(&a, &r))
+}
+
+pub fn unwrap_kwp<C: BlockCipher<16>>(cipher: &C, wrapped: &[u8]) -> Result<SecretBytes, KeyWrapError> {
+    if wrapped.len() < 16 || !wrapped.len().is_multiple_of(8) {
+        return Err(KeyWrapError::InvalidLength);
+    }
+    let (a, padded) = if wrapped.len() == 16 {
+        let b = Zeroizing::new(cipher.decrypt(wrapped.try_into().unwrap()));
+        (b[..8].try_into().unwrap(), Zeroizing::new(b[8..].to_vec()))
+    } else {
+        let mut r = Zeroizing::new(to_semiblocks(&wrapped[8..]));
+        let a = unwrap_core(cipher, wrapped[..8].try_into().unwrap(), &mut r);
+        (a, Zeroizing::new(r.concat()))
+    };
+    //проверка ICV, длины и нулевого дополнения
+    let mli = u32::from_be_bytes(a[4..].try_into().unwrap()) as usize;
+    if !ct_eq(&a[..4], &KWP_ICV) || mli > padded.len() || mli + 8 <= padded.len() {
+        return Err(KeyWrapError::IntegrityCheckFailed);
+    }
+    if !ct_eq(&padded[mli..], &vec![0u8; padded.len() - mli]) {
+        return Err(KeyWrapError::IntegrityCheckFailed);
+    }
+    Ok(SecretBytes::from(padded[..mli].to_vec()))
+}
+
+//обёртывание на мастер-ключе AES-192 (например, ключей из generate_key)
+pub fn wrap_aes192_kw(key_data: &[u8], kek: &SecretKey<24>) -> Result<Vec<u8>, KeyWrapError> {
+    wrap_kw(&Aes192::new(kek.expose_secret().into()), key_data)
+}
+
+pub fn unwrap_aes192_kw(wrapped: &[u8], kek: &SecretKey<24>) -> Result<SecretBytes, KeyWrapError> {
+    unwrap_kw(&Aes192::new(kek.expose_secret().into()), wrapped)
+}
+
+pub fn wrap_aes192_kwp(key_data: &[u8], kek: &SecretKey<24>) -> Result<Vec<u8>, KeyWrapError> {
+    wrap_kwp(&Aes192::new(kek.expose_secret().into()), key_data)
+}
+
+pub fn unwrap_aes192_kwp(wrapped: &[u8], kek: &SecretKey<24>) -> Result<SecretBytes, KeyWrapError> {
+    unwrap_kwp(&Aes192::new(kek.expose_secret().into()), wrapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::{Aes128, Aes256};
+
+    fn kek192(hex_key: &str) -> SecretKey<24> {
+        SecretKey::from_slice(&hex::decode(hex_key).unwrap()).unwrap()
+    }
+
+    //векторы из раздела 4 RFC 3394
+    #[test]
+    fn test_kw_rfc3394_vectors() {
+        let data = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let k128: [u8; 16] = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap().try_into().unwrap();
+        let wrapped = wrap_kw(&Aes128::new(&k128.into()), &data).unwrap();
+        assert_eq!(hex::encode(&wrapped), "1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5");
+        assert_eq!(unwrap_kw(&Aes128::new(&k128.into()), &wrapped).unwrap().expose_secret(), data);
+
+        let k192 = kek192("000102030405060708090a0b0c0d0e0f1011121314151617");
+        let wrapped = wrap_aes192_kw(&data, &k192).unwrap();
+        assert_eq!(hex::encode(&wrapped), "96778b25ae6ca435f92b5b97c050aed2468ab8a17ad84e5d");
+        assert_eq!(unwrap_aes192_kw(&wrapped, &k192).unwrap().expose_secret(), data);
+
+        let k256: [u8; 32] = hex::decode("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f").unwrap().try_into().unwrap();
+        let data256 = hex::decode("00112233445566778899aabbccddeeff000102030405060708090a0b0c0d0e0f").unwrap();
+        let wrapped = wrap_kw(&Aes256::new(&k256.into()), &data256).unwrap();
+        assert_eq!(hex::encode(&wrapped), "28c9f404c4b810f4cbccb35cfb87f8263f5786e2d80ed326cbc7f0e71a99f43bfb988b9b7a02dd21");
+        assert_eq!(unwrap_kw(&Aes256::new(&k256.into()), &wrapped).unwrap().expose_secret(), data256);
+    }
+
+    //векторы из раздела 6 RFC 5649
+    #[test]
+    fn test_kwp_rfc5649_vectors() {
+        let kek = kek192("5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8");
+        let key20 = hex::decode("c37b7e6492584340bed12207808941155068f738").unwrap();
+        let wrapped = wrap_aes192_kwp(&key20, &kek).unwrap();
+        assert_eq!(hex::encode(&wrapped), "138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a");
+        assert_eq!(unwrap_aes192_kwp(&wrapped, &kek).unwrap().expose_secret(), key20);
+
+        let key7 = hex::decode("466f7250617369").unwrap();
+        let wrapped = wrap_aes192_kwp(&key7, &kek).unwrap();
+        assert_eq!(hex::encode(&wrapped), "afbeb0f07dfbf5419200f2ccb50bb24f");
+        assert_eq!(unwrap_aes192_kwp(&wrapped, &kek).unwrap().expose_secret(), key7);
+    }
+
+    #[test]
+    fn test_key_wrap_errors() {
+        let kek = crate::generate_key();
+        let data_key = crate::generate_key();
+        let wrapped = wrap_aes192_kw(data_key.expose_secret(), &kek).unwrap();
+        assert_eq!(wrapped.len(), 32);
+        assert_eq!(unwrap_aes192_kw(&wrapped, &crate::generate_key()), Err(KeyWrapError::IntegrityCheckFailed));
+        assert_eq!(unwrap_aes192_kw(&wrapped[..20], &kek), Err(KeyWrapError::InvalidLength));
+        let mut broken = wrapped.clone();
+        broken[10] ^= 1;
+        assert_eq!(unwrap_aes192_kw(&broken, &kek), Err(KeyWrapError::IntegrityCheckFailed));
+        //KW-обёртка не принимается как KWP: отличается ICV
+        assert_eq!(unwrap_aes192_kwp(&wrapped, &kek), Err(KeyWrapError::IntegrityCheckFailed));
+
+        let wrapped = wrap_aes192_kwp(b"short", &kek).unwrap();
+        assert_eq!(unwrap_aes192_kwp(&wrapped, &kek).unwrap().expose_secret(), b"short");
+        assert_eq!(unwrap_aes192_kwp(&wrapped[..15], &kek), Err(KeyWrapError::InvalidLength));
+
+        //ключи, которые режим не может обернуть: KW - не кратный 8 байтам, KWP - пустой
+        assert_eq!(wrap_aes192_kw(&[0x11; 20], &kek), Err(KeyWrapError::InvalidLength));
+        assert_eq!(wrap_aes192_kw(&[0x11; 8], &kek), Err(KeyWrapError::InvalidLength));
+        assert_eq!(wrap_aes192_kwp(&[], &kek), Err(KeyWrapError::InvalidLength));
+        //20 байт, которые KW не принимает, оборачиваются через KWP
+        assert_eq!(wrap_aes192_kwp(&[0x11; 20], &kek).unwrap().len(), 32);
+    }
+}
@@ -0,0 +1,133 @@
+//Имитовставки CBC-MAC и OMAC (CMAC) - те же, что в лабораторной imito,
+//но поверх BlockCipher<16>, чтобы на них можно было строить режимы CCM и EAX
+use crate::{BlockCipher, SecretKey};
+use aes::Aes192;
+use cipher::KeyInit;
+
+//умножение на x в GF(2^128) (то же, что temp() в imito) - выработка подключей OMAC
+pub fn double_block(block: &[u8; 16]) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    let mut carry = 0u8;
+    for i in (0..16).rev() {
+        let b = block[i];
+        out[i] = (b << 1) | carry;
+        carry = (b & 0x80) >> 7;
+    }
+    //без ветвления: 0x87 добавляется, только если старший бит был единицей
+    out[15] ^= 0x87 & 0u8.wrapping_sub(carry);
+    out
+}
+
+//генерация подключей K1, K2 для OMAC
+fn gen_keys<C: BlockCipher<16>>(cipher: &C) -> ([u8; 16], [u8; 16]) {
+    let l = cipher.encrypt(&[0u8; 16]);
+    let k1 = double_block(&l);
+    let k2 = double_block(&k1);
+    (k1, k2)
+}
+
+//CBC-MAC без дополнения: длина данных должна быть кратна блоку
+//(в CCM дополнение выполняет функция форматирования)
+pub fn cbc_mac<C: BlockCipher<16>>(cipher: &C, data: &[u8]) -> [u8; 16] {
+    assert_eq!(data.len() % 16, 0, "CBC-MAC принимает только целые блоки");
+    let mut result = [0u8; 16];
+    for block in data.chunks(16) {
+        for i in 0..16 {
+            result[i] ^= block[i];
+        }
+        result = cipher.encrypt(&result);
+    }
+    result
+}
+
+//OMAC1 (CMAC, NIST SP 800-38B)
+pub fn omac<C: BlockCipher<16>>(cipher: &C, data: &[u8]) -> [u8; 16] {
+    let (k1, k2) = gen_keys(cipher);
+    omac_with_keys(cipher, &k1, &k2, data)
+}
+
+fn omac_with_keys<C: BlockCipher<16>>(cipher: &C, k1: &[u8; 16], k2: &[u8; 16], data: &[u8]) -> [u8; 16] {
+    let n = data.len().div_ceil(16).max(1);
+    let mut result = [0u8; 16];
+    //все блоки кроме последнего
+    for block in data.chunks(16).take(n - 1) {
+        for i in 0..16 {
+            result[i] ^= block[i];
+        }
+        result = cipher.encrypt(&result);
+    }
+    //последний блок: полный -> XOR с K1, неполный (или пустое сообщение) -> паддинг 10... и XOR с K2
+    let last = &data[(n - 1) * 16..];
+    let mut last_block = [0u8; 16];
+    last_block[..last.len()].copy_from_slice(last);
+    let subkey = if last.len() == 16 {
+        k1
+    } else {
+        last_block[last.len()] = 0x80;
+        k2
+    };
+    for i in 0..16 {
+        result[i] ^= last_block[i] ^ subkey[i];
+    }
+    cipher.encrypt(&result)
+}
+
+//OMAC с готовыми подключами: ключ расширяется и K1, K2 вырабатываются один раз на объект,
+//что заметно на коротких сообщениях (одно лишнее зашифрование на 16 байт - это половина работы)
+pub struct Omac<C: BlockCipher<16>> {
+    cipher: C,
+    k1: [u8; 16],
+    k2: [u8; 16],
+}
+
+impl<C: BlockCipher<16>> Omac<C> {
+    pub fn new(cipher: C) -> Self {
+        let (k1, k2) = gen_keys(&cipher);
+        Omac { cipher, k1, k2 }
+    }
+
+    pub fn mac(&self, data: &[u8]) -> [u8; 16] {
+        omac_with_keys(&self.cipher, &self.k1, &self.k2, data)
+    }
+
+    pub fn verify(&self, data: &[u8], tag: &[u8]) -> bool {
+        crate::ct_eq(&self.mac(data), tag)
+    }
+}
+
+pub fn aes192_omac(key: &SecretKey<24>) -> Omac<Aes192> {
+    Omac::new(Aes192::new(key.expose_secret().into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::Aes128;
+    use cipher::KeyInit;
+
+    //примеры из NIST SP 800-38B для AES-128
+    #[test]
+    fn test_omac_aes128_vectors() {
+        let key: [u8; 16] = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap().try_into().unwrap();
+        let cipher = Aes128::new(&key.into());
+        let m = hex::decode("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e5130c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710").unwrap();
+        assert_eq!(hex::encode(omac(&cipher, &[])), "bb1d6929e95937287fa37d129b756746");
+        assert_eq!(hex::encode(omac(&cipher, &m[..16])), "070a16b46b4d4144f79bdd9dd04a287c");
+        assert_eq!(hex::encode(omac(&cipher, &m[..40])), "dfa66747de9ae63030ca32611497c827");
+        assert_eq!(hex::encode(omac(&cipher, &m)), "51f0bebf7e3b9d92fc49741779363cfe");
+    }
+
+    //один объект на все сообщения даёт те же тэги
+    #[test]
+    fn test_omac_context_reuse() {
+        let key: [u8; 16] = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap().try_into().unwrap();
+        let omac_ctx = Omac::new(Aes128::new(&key.into()));
+        let m = hex::decode("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51").unwrap();
+        assert_eq!(hex::encode(omac_ctx.mac(&[])), "bb1d6929e95937287fa37d129b756746");
+        assert_eq!(hex::encode(omac_ctx.mac(&m[..16])), "070a16b46b4d4144f79bdd9dd04a287c");
+        assert!(omac_ctx.verify(&m[..16], &hex::decode("070a16b46b4d4144f79bdd9dd04a287c").unwrap()));
+        assert!(!omac_ctx.verify(&m[..15], &hex::decode("070a16b46b4d4144f79bdd9dd04a287c").unwrap()));
+        let key = crate::generate_key();
+        assert_eq!(aes192_omac(&key).mac(&m), omac(&Aes192::new(key.expose_secret().into()), &m));
+    }
+}
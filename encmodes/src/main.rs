@@ -1,7 +1,37 @@
-use aes::Aes192;
+pub mod acpkm;
+pub mod bench;
+pub mod cbc_cs;
+pub mod cavp;
+pub mod cbc_ecb_attacks;
+pub mod ccm;
+pub mod container;
+pub mod ctr;
+pub mod cfb_ofb;
+pub mod chacha20;
+pub mod eax;
+pub mod ecb_image;
+pub mod fpe;
+pub mod gcm;
+pub mod gcm_siv;
+pub mod gost;
+pub mod kw;
+pub mod mac;
+pub mod mgm;
+pub mod nonce_reuse;
+pub mod ocb;
+pub mod padding_oracle;
+pub mod parallel;
+pub mod pbe;
+pub mod secret;
+pub mod siv;
+pub mod stream;
+pub mod xts;
+
+pub use secret::{SecretBytes, SecretKey};
+
+use aes::{Aes128, Aes192, Aes256};
 use cipher::{KeyInit, BlockCipherEncrypt, BlockCipherDecrypt};
 use rand::Rng;
-use std::time::Instant;
 
 //генерация iv
 pub fn generate_iv() -> [u8; 16] {
@@ -18,11 +48,8 @@ pub fn generate_iv_ctr() -> [u8; 8] {
 }
 
 //генерация ключа
-pub fn generate_key() -> [u8; 24] {
-    let mut rng = rand::rng();
-    let mut key = [0u8; 24];
-    rng.fill(&mut key);
-    key
+pub fn generate_key() -> SecretKey<24> {
+    SecretKey::generate()
 }
 
 //функция генерации раундового ключа (nonce+counter) для ctr (также 16 байт)
@@ -62,6 +89,45 @@ pub fn aes192_decrypt_block(cipher: &Aes192, block: &[u8; 16]) -> [u8; 16] {
     buf.into()
 }
 
+//общий интерфейс блочного шифра с блоком N байт - на нём построены режимы в отдельных модулях,
+//чтобы их можно было проверять на тестовых векторах не только для AES192
+pub trait BlockCipher<const N: usize> {
+    fn encrypt(&self, block: &[u8; N]) -> [u8; N];
+    fn decrypt(&self, block: &[u8; N]) -> [u8; N];
+}
+
+macro_rules! impl_block_cipher_aes {
+    ($($t:ty),*) => {
+        $(
+            impl BlockCipher<16> for $t {
+                fn encrypt(&self, block: &[u8; 16]) -> [u8; 16] {
+                    let mut buf = *block;
+                    self.encrypt_block((&mut buf).into());
+                    buf
+                }
+                fn decrypt(&self, block: &[u8; 16]) -> [u8; 16] {
+                    let mut buf = *block;
+                    self.decrypt_block((&mut buf).into());
+                    buf
+                }
+            }
+        )*
+    };
+}
+impl_block_cipher_aes!(Aes128, Aes192, Aes256);
+
+//сравнение за время, не зависящее от содержимого (для проверки имитовставок)
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
 //расширение открытого текста
 pub fn pkcs7_pad(data: &[u8], block_size: usize) -> Vec<u8> {
     assert!(block_size <= 255); //так как дополняем последний блок, его длина явно меньше 255, поэтому такое дополнение подходит
@@ -71,173 +137,314 @@ pub fn pkcs7_pad(data: &[u8], block_size: usize) -> Vec<u8> {
     padded
 }
 
-//шифрование ECB
-pub fn encrypt_aes192_ecb(data: &[u8], key : &[u8; 24]) -> Vec<u8> {
-    let  cipher = Aes192::new(key.into());
-    let mut res= Vec::new();
-    let padded_data = pkcs7_pad(data, 16);
-    for block in padded_data.chunks(16) {
-        let mut block_array = [0u8; 16];
-        block_array[0..block.len()].copy_from_slice(block);
-        let new_block =  aes192_encrypt_block(&cipher, &block_array);
-        res.extend_from_slice(&new_block);
+//снятие дополнения, None если дополнение некорректно
+pub fn pkcs7_unpad(data: &[u8], block_size: usize) -> Option<Vec<u8>> {
+    let pad = *data.last()? as usize;
+    if pad == 0 || pad > block_size || pad > data.len() || !data[data.len() - pad..].iter().all(|&b| b as usize == pad) {
+        return None;
+    }
+    Some(data[..data.len() - pad].to_vec())
+}
+
+//Контексты режимов с расширенным ключом: Aes192::new выполняется один раз в new,
+//дальше объект шифрует сколько угодно сообщений. Функции encrypt_aes192_* ниже - обёртки над ними.
+//from_cipher принимает любой BlockCipher<16> - так режимы проверяются на векторах AES-128 и AES-256
+pub struct EcbCipher<C: BlockCipher<16> = Aes192> {
+    cipher: C,
+}
 
+impl EcbCipher {
+    pub fn new(key: &SecretKey<24>) -> Self {
+        EcbCipher { cipher: Aes192::new(key.expose_secret().into()) }
     }
-    res
 }
 
-//расшифрование ECB
-pub fn decrypt_aes192_ecb(data: &[u8], key : &[u8; 24]) -> Vec<u8> {
-    let  cipher = Aes192::new(key.into());
-    let mut res= Vec::new();
-    for block in data.chunks(16) {
-        let mut block_array = [0u8; 16];
-        block_array[0..block.len()].copy_from_slice(block);
-        let new_block =  aes192_decrypt_block(&cipher, &block_array);
-        res.extend_from_slice(&new_block);
+impl<C: BlockCipher<16>> EcbCipher<C> {
+    pub fn from_cipher(cipher: C) -> Self {
+        EcbCipher { cipher }
+    }
+
+    //шифрование ECB
+    pub fn encrypt(&self, data: &[u8]) -> Vec<u8> {
+        let mut res= Vec::new();
+        let padded_data = pkcs7_pad(data, 16);
+        for block in padded_data.chunks(16) {
+            let mut block_array = [0u8; 16];
+            block_array[0..block.len()].copy_from_slice(block);
+            let new_block =  self.cipher.encrypt(&block_array);
+            res.extend_from_slice(&new_block);
+        }
+        res
+    }
+
+    //расшифрование ECB
+    pub fn decrypt(&self, data: &[u8]) -> Vec<u8> {
+        let mut res= Vec::new();
+        for block in data.chunks(16) {
+            let mut block_array = [0u8; 16];
+            block_array[0..block.len()].copy_from_slice(block);
+            let new_block =  self.cipher.decrypt(&block_array);
+            res.extend_from_slice(&new_block);
+        }
+        res
     }
-    res
 }
 
-//шифрование CBC
-pub fn encrypt_aes192_cbc(data: &[u8], key : &[u8; 24], iv: &[u8; 16]) -> Vec<u8> {
-    let  cipher = Aes192::new(key.into());
-    let mut res= Vec::new();
-    let mut r = *iv;
-    let padded_data = pkcs7_pad(data, 16);
-    for block in padded_data.chunks(16) {
-        let mut block_array = [0u8; 16];
-        block_array[0..block.len()].copy_from_slice(block);
-        let new_block = xor_arrays(block_array , r);
-        let res_block = aes192_encrypt_block(&cipher, &new_block);
-        res.extend_from_slice(&res_block);
-        r = res_block;
+pub struct CbcCipher<C: BlockCipher<16> = Aes192> {
+    cipher: C,
+}
+
+impl CbcCipher {
+    pub fn new(key: &SecretKey<24>) -> Self {
+        CbcCipher { cipher: Aes192::new(key.expose_secret().into()) }
     }
-    res
 }
 
-//расшифрование CBC
-pub fn decrypt_aes192_cbc(data: &[u8], key : &[u8; 24], iv: &[u8; 16]) -> Vec<u8> {
-    let  cipher = Aes192::new(key.into());
-    let mut res= Vec::new();
-    let mut r = *iv;
-    for block in data.chunks(16) {
-        let mut block_array = [0u8; 16];
-        block_array[0..block.len()].copy_from_slice(block);
-        let decrypt_block = aes192_decrypt_block(&cipher, &block_array);
-        let res_block = xor_arrays(decrypt_block , r);
-        res.extend_from_slice(&res_block);
-        r = block_array;
+impl<C: BlockCipher<16>> CbcCipher<C> {
+    pub fn from_cipher(cipher: C) -> Self {
+        CbcCipher { cipher }
+    }
+
+    //шифрование CBC
+    pub fn encrypt(&self, data: &[u8], iv: &[u8; 16]) -> Vec<u8> {
+        let mut res= Vec::new();
+        let mut r = *iv;
+        let padded_data = pkcs7_pad(data, 16);
+        for block in padded_data.chunks(16) {
+            let mut block_array = [0u8; 16];
+            block_array[0..block.len()].copy_from_slice(block);
+            let new_block = xor_arrays(block_array , r);
+            let res_block = self.cipher.encrypt(&new_block);
+            res.extend_from_slice(&res_block);
+            r = res_block;
+        }
+        res
+    }
+
+    //расшифрование CBC
+    pub fn decrypt(&self, data: &[u8], iv: &[u8; 16]) -> Vec<u8> {
+        let mut res= Vec::new();
+        let mut r = *iv;
+        for block in data.chunks(16) {
+            let mut block_array = [0u8; 16];
+            block_array[0..block.len()].copy_from_slice(block);
+            let decrypt_block = self.cipher.decrypt(&block_array);
+            let res_block = xor_arrays(decrypt_block , r);
+            res.extend_from_slice(&res_block);
+            r = block_array;
+        }
+        res
     }
-    res
 }
 
-//шифрование CFB
-pub fn encrypt_aes192_cfb(data: &[u8], key : &[u8; 24], iv: &[u8; 16]) -> Vec<u8> {
-    let  cipher = Aes192::new(key.into());
-    let mut res= Vec::new();
-    let mut r = *iv;
-    let padded_data = pkcs7_pad(data, 16);
-    for block in padded_data.chunks(16) {
-        let mut block_array = [0u8; 16];
-        block_array[0..block.len()].copy_from_slice(block);
-        let encrypt_block = aes192_encrypt_block(&cipher, &r);
-        let res_block = xor_arrays(block_array , encrypt_block);
-        res.extend_from_slice(&res_block);
-        r = res_block;
+pub struct CfbCipher<C: BlockCipher<16> = Aes192> {
+    cipher: C,
+}
+
+impl CfbCipher {
+    pub fn new(key: &SecretKey<24>) -> Self {
+        CfbCipher { cipher: Aes192::new(key.expose_secret().into()) }
     }
-    res
 }
 
-//расшифрование CFB
-pub fn decrypt_aes192_cfb(data: &[u8], key : &[u8; 24], iv: &[u8; 16]) -> Vec<u8> {
-    let  cipher = Aes192::new(key.into());
-    let mut res= Vec::new();
-    let mut r = *iv;
-    for block in data.chunks(16) {
-        let mut block_array = [0u8; 16];
-        block_array[0..block.len()].copy_from_slice(block);
-        let decrypt_block = aes192_encrypt_block(&cipher, &r);
-        let res_block = xor_arrays(block_array , decrypt_block);
-        res.extend_from_slice(&res_block);
-        r = block_array;
+impl<C: BlockCipher<16>> CfbCipher<C> {
+    pub fn from_cipher(cipher: C) -> Self {
+        CfbCipher { cipher }
+    }
+
+    //шифрование CFB
+    pub fn encrypt(&self, data: &[u8], iv: &[u8; 16]) -> Vec<u8> {
+        let mut res= Vec::new();
+        let mut r = *iv;
+        let padded_data = pkcs7_pad(data, 16);
+        for block in padded_data.chunks(16) {
+            let mut block_array = [0u8; 16];
+            block_array[0..block.len()].copy_from_slice(block);
+            let encrypt_block = self.cipher.encrypt(&r);
+            let res_block = xor_arrays(block_array , encrypt_block);
+            res.extend_from_slice(&res_block);
+            r = res_block;
+        }
+        res
+    }
+
+    //расшифрование CFB
+    pub fn decrypt(&self, data: &[u8], iv: &[u8; 16]) -> Vec<u8> {
+        let mut res= Vec::new();
+        let mut r = *iv;
+        for block in data.chunks(16) {
+            let mut block_array = [0u8; 16];
+            block_array[0..block.len()].copy_from_slice(block);
+            let decrypt_block = self.cipher.encrypt(&r);
+            let res_block = xor_arrays(block_array , decrypt_block);
+            res.extend_from_slice(&res_block);
+            r = block_array;
+        }
+        res
     }
-    res
 }
 
-//шифрование OFB
-pub fn encrypt_aes192_ofb(data: &[u8], key : &[u8; 24], iv: &[u8; 16]) -> Vec<u8> {
-    let  cipher = Aes192::new(key.into());
-    let mut res= Vec::new();
-    let mut r = *iv;
-    let padded_data = pkcs7_pad(data, 16);
-    for block in padded_data.chunks(16) {
-        let encrypt_block = aes192_encrypt_block(&cipher, &r);
-        let mut block_array = [0u8; 16];
-        block_array[0..block.len()].copy_from_slice(block);
-        let res_block = xor_arrays(block_array , encrypt_block);
-        res.extend_from_slice(&res_block);
-        r = encrypt_block;
+pub struct OfbCipher<C: BlockCipher<16> = Aes192> {
+    cipher: C,
+}
+
+impl OfbCipher {
+    pub fn new(key: &SecretKey<24>) -> Self {
+        OfbCipher { cipher: Aes192::new(key.expose_secret().into()) }
     }
-    res
 }
 
-//расшифрование OFB
-pub fn decrypt_aes192_ofb(data: &[u8], key : &[u8; 24], iv: &[u8; 16]) -> Vec<u8> {
-    let  cipher = Aes192::new(key.into());
-    let mut res= Vec::new();
-    let mut r = *iv;
-    for block in data.chunks(16) {
-        let encrypt_block = aes192_encrypt_block(&cipher, &r);
-        if block.len() != 16 {
-            pkcs7_pad(block, 16);
+impl<C: BlockCipher<16>> OfbCipher<C> {
+    pub fn from_cipher(cipher: C) -> Self {
+        OfbCipher { cipher }
+    }
+
+    //шифрование OFB
+    pub fn encrypt(&self, data: &[u8], iv: &[u8; 16]) -> Vec<u8> {
+        let mut res= Vec::new();
+        let mut r = *iv;
+        let padded_data = pkcs7_pad(data, 16);
+        for block in padded_data.chunks(16) {
+            let encrypt_block = self.cipher.encrypt(&r);
+            let mut block_array = [0u8; 16];
+            block_array[0..block.len()].copy_from_slice(block);
+            let res_block = xor_arrays(block_array , encrypt_block);
+            res.extend_from_slice(&res_block);
+            r = encrypt_block;
         }
-        let mut block_array = [0u8; 16];
-        block_array[0..block.len()].copy_from_slice(block);
-        let res_block = xor_arrays(block_array , encrypt_block);
-        res.extend_from_slice(&res_block);
-        r = encrypt_block;
+        res
+    }
+
+    //расшифрование OFB
+    pub fn decrypt(&self, data: &[u8], iv: &[u8; 16]) -> Vec<u8> {
+        let mut res= Vec::new();
+        let mut r = *iv;
+        for block in data.chunks(16) {
+            let encrypt_block = self.cipher.encrypt(&r);
+            let mut block_array = [0u8; 16];
+            block_array[0..block.len()].copy_from_slice(block);
+            let res_block = xor_arrays(block_array , encrypt_block);
+            res.extend_from_slice(&res_block);
+            r = encrypt_block;
+        }
+        res
     }
-    res
 }
 
-//шифрование CTR
-pub fn encrypt_aes192_ctr(data: &[u8], key : &[u8; 24], iv: &[u8; 8]) -> Vec<u8> {
-    let  cipher = Aes192::new(key.into());
-    let mut res= Vec::new();
-    let mut counter = 0;
-    let mut r = generate_nc(*iv, counter);
-    let padded_data = pkcs7_pad(data, 16);
-    for block in padded_data.chunks(16) {
-        let encrypt_block = aes192_encrypt_block(&cipher, &r);
-        let mut block_array = [0u8; 16];
-        block_array[0..block.len()].copy_from_slice(block);
-        let res_block = xor_arrays(block_array , encrypt_block);
-        res.extend_from_slice(&res_block);
-        counter += 1;
-        r = generate_nc(*iv, counter);
+pub struct CtrCipher<C: BlockCipher<16> = Aes192> {
+    cipher: C,
+}
+
+impl CtrCipher {
+    pub fn new(key: &SecretKey<24>) -> Self {
+        CtrCipher { cipher: Aes192::new(key.expose_secret().into()) }
     }
-    res
 }
-//расшифрование CTR
-pub fn decrypt_aes192_ctr(data: &[u8], key : &[u8; 24], iv: &[u8; 8]) -> Vec<u8> {
-    let  cipher = Aes192::new(key.into());
-    let mut res= Vec::new();
-    let mut counter = 0;
-    let mut r = generate_nc(*iv, counter);
-    for block in data.chunks(16) {
-        let encrypt_block = aes192_encrypt_block(&cipher, &r);
-        let mut block_array = [0u8; 16];
-        block_array[0..block.len()].copy_from_slice(block);
-        let res_block = xor_arrays(block_array , encrypt_block);
-        res.extend_from_slice(&res_block);
-        counter += 1;
-        r = generate_nc(*iv, counter);
+
+impl<C: BlockCipher<16>> CtrCipher<C> {
+    pub fn from_cipher(cipher: C) -> Self {
+        CtrCipher { cipher }
+    }
+
+    //шифрование CTR
+    pub fn encrypt(&self, data: &[u8], iv: &[u8; 8]) -> Vec<u8> {
+        self.apply(&pkcs7_pad(data, 16), iv)
     }
-    res
+
+    //расшифрование CTR
+    pub fn decrypt(&self, data: &[u8], iv: &[u8; 8]) -> Vec<u8> {
+        self.apply(data, iv)
+    }
+
+    fn apply(&self, data: &[u8], iv: &[u8; 8]) -> Vec<u8> {
+        let mut res= Vec::new();
+        let mut counter = 0;
+        let mut r = generate_nc(*iv, counter);
+        for block in data.chunks(16) {
+            let encrypt_block = self.cipher.encrypt(&r);
+            let mut block_array = [0u8; 16];
+            block_array[0..block.len()].copy_from_slice(block);
+            let res_block = xor_arrays(block_array , encrypt_block);
+            res.extend_from_slice(&res_block);
+            counter += 1;
+            r = generate_nc(*iv, counter);
+        }
+        res
+    }
+}
+
+//шифрование ECB
+pub fn encrypt_aes192_ecb(data: &[u8], key : &SecretKey<24>) -> Vec<u8> {
+    EcbCipher::new(key).encrypt(data)
+}
+
+//расшифрование ECB
+pub fn decrypt_aes192_ecb(data: &[u8], key : &SecretKey<24>) -> Vec<u8> {
+    EcbCipher::new(key).decrypt(data)
+}
+
+//шифрование CBC
+pub fn encrypt_aes192_cbc(data: &[u8], key : &SecretKey<24>, iv: &[u8; 16]) -> Vec<u8> {
+    CbcCipher::new(key).encrypt(data, iv)
+}
+
+//расшифрование CBC
+pub fn decrypt_aes192_cbc(data: &[u8], key : &SecretKey<24>, iv: &[u8; 16]) -> Vec<u8> {
+    CbcCipher::new(key).decrypt(data, iv)
+}
+
+//шифрование CFB
+pub fn encrypt_aes192_cfb(data: &[u8], key : &SecretKey<24>, iv: &[u8; 16]) -> Vec<u8> {
+    CfbCipher::new(key).encrypt(data, iv)
+}
+
+//расшифрование CFB
+pub fn decrypt_aes192_cfb(data: &[u8], key : &SecretKey<24>, iv: &[u8; 16]) -> Vec<u8> {
+    CfbCipher::new(key).decrypt(data, iv)
+}
+
+//шифрование OFB
+pub fn encrypt_aes192_ofb(data: &[u8], key : &SecretKey<24>, iv: &[u8; 16]) -> Vec<u8> {
+    OfbCipher::new(key).encrypt(data, iv)
+}
+
+//расшифрование OFB
+pub fn decrypt_aes192_ofb(data: &[u8], key : &SecretKey<24>, iv: &[u8; 16]) -> Vec<u8> {
+    OfbCipher::new(key).decrypt(data, iv)
+}
+
+//шифрование CTR
+pub fn encrypt_aes192_ctr(data: &[u8], key : &SecretKey<24>, iv: &[u8; 8]) -> Vec<u8> {
+    CtrCipher::new(key).encrypt(data, iv)
+}
+//расшифрование CTR
+pub fn decrypt_aes192_ctr(data: &[u8], key : &SecretKey<24>, iv: &[u8; 8]) -> Vec<u8> {
+    CtrCipher::new(key).decrypt(data, iv)
 }
 
 fn main() {
+    //cargo run -- image penguin.ppm - шифрование картинки всеми режимами (см. ecb_image)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(|s| s.as_str()) == Some("image") {
+        if let Err(e) = ecb_image::run_image_tool(&args[1..]) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    //cargo run -- kat - прогон векторов NIST из vectors/ (см. cavp)
+    if args.first().map(|s| s.as_str()) == Some("kat") {
+        if !cavp::run_all() {
+            std::process::exit(1);
+        }
+        return;
+    }
+    //cargo run --release -- bench [максимальный размер] - замеры всех режимов (см. bench)
+    if args.first().map(|s| s.as_str()) == Some("bench") {
+        bench::run(&args[1..]);
+        return;
+    }
+
     let message =
         "Стояла зима.
         Дул ветер из степи.
@@ -364,65 +571,13 @@ fn main() {
     let cbc_plaintext = decrypt_aes192_cbc(&*cbc_ciphertext, &key, &_iv);
     println!("Расшифровка сообщения:, {:?}", String::from_utf8(cbc_plaintext));
 
-
-
-    //Будем замерять время на тысяче шифрованиях и расшифрованиях
-    //стихотворения Бориса Пастернака "Рождественская звезда"
-    let start = Instant::now();
-    for _i in 0..1000 {
-        let ctr_ciphertext = encrypt_aes192_ctr(message.as_ref(), &key, &_iv2);
-        let ctr_plaintext = decrypt_aes192_ctr(&*ctr_ciphertext, &key, &_iv2);
-        //println!("Шифртекст ctr:, {:?}", ctr_ciphertext);
-        //println!("Расшифрованный текст ctr:, {:?}", String::from_utf8(ctr_plaintext));
-    }
-    let duration = start.elapsed();
-    println!("Время ctr:, {:?}", duration);
-    let start = Instant::now();
-    for _i in 0..1000 {
-        let cbc_ciphertext = encrypt_aes192_cbc(message.as_ref(), &key, &_iv);
-        let cbc_plaintext = decrypt_aes192_cbc(&*cbc_ciphertext, &key, &_iv);
-        //println!("Шифртекст cbc:, {:?}", cbc_ciphertext);
-        //println!("Расшифрованный текст cbc:, {:?}", String::from_utf8(cbc_plaintext));
-    }
-    let duration = start.elapsed();
-    println!("Время cbc:, {:?}", duration);
-    let start = Instant::now();
-    for _i in 0..1000 {
-        let cfb_ciphertext = encrypt_aes192_cfb(message.as_ref(), &key, &_iv);
-        let cfb_plaintext = decrypt_aes192_cfb(&*cfb_ciphertext, &key, &_iv);
-        //println!("Шифртекст cfb:, {:?}", cfb_ciphertext);
-        //println!("Расшифрованный текст cfb:, {:?}", String::from_utf8(cfb_plaintext));
-    }
-    let duration = start.elapsed();
-    println!("Время cfb:, {:?}", duration);
-    let start = Instant::now();
-    for _i in 0..1000 {
-        let ofb_ciphertext = encrypt_aes192_ofb(message.as_ref(), &key, &_iv);
-        let ofb_plaintext = decrypt_aes192_ofb(&*ofb_ciphertext, &key, &_iv);
-        //println!("Шифртекст ofb:, {:?}", ofb_ciphertext);
-        //println!("Расшифрованный текст ofb:, {:?}", String::from_utf8(ofb_plaintext));
-    }
-    let duration = start.elapsed();
-    println!("Время ofb:, {:?}", duration);
-    let start = Instant::now();
-    for _i in 0..1000 {
-        let ecb_ciphertext = encrypt_aes192_ecb(message.as_ref(), &key);
-        let ecb_plaintext = decrypt_aes192_ecb(&*ecb_ciphertext, &key);
-        //println!("Шифртекст ecb:, {:?}", ecb_ciphertext);
-        //println!("Расшифрованный текст ecb:, {:?}", String::from_utf8(ecb_plaintext));
-    }
-    let duration = start.elapsed();
-    println!("Время ecb:, {:?}", duration);
-    let  cipher = Aes192::new(&key.into());
-    let start = Instant::now();
-    for _i in 0..1000 {
-        aes192_encrypt_block(&cipher, &_iv);
-        aes192_encrypt_block(&cipher, &_iv);
-    }
-    let duration = start.elapsed();
-    println!("Время шифрования одного блока:, {:?}", duration);
+    //Контейнер хранит режим, nonce и размер куска вместе с шифртекстом - для расшифрования нужен только ключ
+    let header = container::Header::new(container::AeadMode::Gcm, 4096, b"pasternak.txt");
+    let encrypted = container::encrypt_aes192_container(message.as_ref(), &key, header).unwrap();
+    let (header, plaintext) = container::decrypt_aes192_container(&encrypted, &key).unwrap();
+    println!("Контейнер: {} байт, режим {:?}, совпадает с исходным: {}", encrypted.len(), header.mode, plaintext == message.as_bytes());
 }
-//В выводе можно видеть:
+//В выводе cargo run --release -- bench можно видеть:
 //ecb самый быстрый - там минимальное количество операций, данные блока зашифрования никуда не передаются
 //ofb, cfb, cbc раюотают примерно за одинаковое время, потому что по структуре и по операциям одинаковые
 //ctr работает немного дольше, из-за необходимости дополнительной генерации раундового ключа, который iv/2 | counter
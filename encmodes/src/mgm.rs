@@ -0,0 +1,234 @@
+//Режим MGM (Multilinear Galois Mode, Р 1323565.1.026-2019, RFC 9058)
+//Работает для 64-битных (Магма) и 128-битных (Кузнечик, AES) блочных шифров:
+//шифрование - CTR со счётчиком Y, имитовставка - сумма H_i * блок_i в GF(2^n), H_i = E_K(Z_i)
+use crate::gost::{Kuznyechik, Magma};
+use crate::{BlockCipher, SecretKey, ct_eq};
+
+//умножение в GF(2^64) (x^64 + x^4 + x^3 + x + 1) или GF(2^128) (x^128 + x^7 + x^2 + x + 1)
+//элемент хранится в u128, старший бит блока - коэффициент при x^(n-1)
+pub fn gf_mul(x: u128, y: u128, bits: u32) -> u128 {
+    let (poly, width_mask) = match bits {
+        64 => (0x1B, u64::MAX as u128),
+        128 => (0x87, u128::MAX),
+        _ => panic!("MGM определён только для блоков 64 и 128 бит"),
+    };
+    let mut z = 0u128;
+    let mut v = y;
+    for i in 0..bits {
+        z ^= v & 0u128.wrapping_sub((x >> i) & 1);
+        let carry = (v >> (bits - 1)) & 1;
+        v = ((v << 1) & width_mask) ^ (poly & 0u128.wrapping_sub(carry));
+    }
+    z
+}
+
+fn to_u128<const N: usize>(block: &[u8; N]) -> u128 {
+    let mut buf = [0u8; 16];
+    buf[16 - N..].copy_from_slice(block);
+    u128::from_be_bytes(buf)
+}
+
+fn from_u128<const N: usize>(value: u128) -> [u8; N] {
+    value.to_be_bytes()[16 - N..].try_into().unwrap()
+}
+
+//incr_r - увеличение правой половины блока, incr_l - левой (по модулю 2^(n/2))
+fn incr_r<const N: usize>(block: &[u8; N]) -> [u8; N] {
+    let mut res = *block;
+    for i in (N / 2..N).rev() {
+        res[i] = res[i].wrapping_add(1);
+        if res[i] != 0 {
+            break;
+        }
+    }
+    res
+}
+
+fn incr_l<const N: usize>(block: &[u8; N]) -> [u8; N] {
+    let mut res = *block;
+    for i in (0..N / 2).rev() {
+        res[i] = res[i].wrapping_add(1);
+        if res[i] != 0 {
+            break;
+        }
+    }
+    res
+}
+
+//nonce - это (n-1)-битное значение, старший бит должен быть нулевым
+pub fn is_valid_mgm_nonce<const N: usize>(nonce: &[u8; N]) -> bool {
+    nonce[0] & 0x80 == 0
+}
+
+fn check_tag_len<const N: usize>(tag_len: usize) -> bool {
+    (4..=N).contains(&tag_len)
+}
+
+//CTR: Y_1 = E_K(0 || nonce), Y_{i+1} = incr_r(Y_i)
+fn mgm_ctr<C: BlockCipher<N>, const N: usize>(cipher: &C, nonce: &[u8; N], data: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(data.len());
+    let mut y = cipher.encrypt(nonce);
+    for block in data.chunks(N) {
+        let encrypt_block = cipher.encrypt(&y);
+        res.extend(block.iter().zip(encrypt_block.iter()).map(|(a, b)| a ^ b));
+        y = incr_r(&y);
+    }
+    res
+}
+
+//имитовставка: Z_1 = E_K(1 || nonce), блоки aad, шифртекста (дополненные нулями) и len(A) || len(C)
+fn mgm_tag<C: BlockCipher<N>, const N: usize>(cipher: &C, nonce: &[u8; N], aad: &[u8], ciphertext: &[u8]) -> [u8; N] {
+    let bits = (N * 8) as u32;
+    let mut z0 = *nonce;
+    z0[0] |= 0x80;
+    let mut z = cipher.encrypt(&z0);
+    let mut sum = 0u128;
+    let mut absorb = |block: &[u8]| {
+        let mut block_array = [0u8; N];
+        block_array[..block.len()].copy_from_slice(block);
+        let h = cipher.encrypt(&z);
+        sum ^= gf_mul(to_u128(&h), to_u128(&block_array), bits);
+        z = incr_l(&z);
+    };
+    for block in aad.chunks(N) {
+        absorb(block);
+    }
+    for block in ciphertext.chunks(N) {
+        absorb(block);
+    }
+    let half = N / 2;
+    let mut len_block = [0u8; N];
+    let a_len = ((aad.len() as u64) * 8).to_be_bytes();
+    let c_len = ((ciphertext.len() as u64) * 8).to_be_bytes();
+    len_block[..half].copy_from_slice(&a_len[8 - half..]);
+    len_block[half..].copy_from_slice(&c_len[8 - half..]);
+    absorb(&len_block);
+    cipher.encrypt(&from_u128(sum))
+}
+
+//шифрование MGM, возвращает (шифртекст, тэг длины tag_len байт)
+pub fn encrypt_mgm<C: BlockCipher<N>, const N: usize>(
+    cipher: &C,
+    nonce: &[u8; N],
+    aad: &[u8],
+    data: &[u8],
+    tag_len: usize,
+) -> (Vec<u8>, Vec<u8>) {
+    assert!(is_valid_mgm_nonce(nonce), "старший бит nonce MGM должен быть нулевым");
+    assert!(check_tag_len::<N>(tag_len), "длина тэга MGM должна быть от 4 байт до размера блока");
+    let ciphertext = mgm_ctr(cipher, nonce, data);
+    let tag = mgm_tag(cipher, nonce, aad, &ciphertext);
+    (ciphertext, tag[..tag_len].to_vec())
+}
+
+//расшифрование MGM, при некорректном nonce или несовпадении тэга возвращает None
+pub fn decrypt_mgm<C: BlockCipher<N>, const N: usize>(
+    cipher: &C,
+    nonce: &[u8; N],
+    aad: &[u8],
+    data: &[u8],
+    tag: &[u8],
+) -> Option<Vec<u8>> {
+    if !is_valid_mgm_nonce(nonce) || !check_tag_len::<N>(tag.len()) {
+        return None;
+    }
+    let expected = mgm_tag(cipher, nonce, aad, data);
+    if !ct_eq(&expected[..tag.len()], tag) {
+        return None;
+    }
+    Some(mgm_ctr(cipher, nonce, data))
+}
+
+//шифрование Кузнечик-MGM: на выходе шифртекст || тэг 16 байт
+pub fn encrypt_kuznyechik_mgm(data: &[u8], key: &SecretKey<32>, nonce: &[u8; 16], aad: &[u8]) -> Vec<u8> {
+    let cipher = Kuznyechik::new(key.expose_secret());
+    let (mut res, tag) = encrypt_mgm(&cipher, nonce, aad, data, 16);
+    res.extend_from_slice(&tag);
+    res
+}
+
+//расшифрование Кузнечик-MGM: на входе шифртекст || тэг 16 байт
+pub fn decrypt_kuznyechik_mgm(data: &[u8], key: &SecretKey<32>, nonce: &[u8; 16], aad: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 16 {
+        return None;
+    }
+    let cipher = Kuznyechik::new(key.expose_secret());
+    let (ciphertext, tag) = data.split_at(data.len() - 16);
+    decrypt_mgm(&cipher, nonce, aad, ciphertext, tag)
+}
+
+//шифрование Магма-MGM: на выходе шифртекст || тэг 8 байт
+pub fn encrypt_magma_mgm(data: &[u8], key: &SecretKey<32>, nonce: &[u8; 8], aad: &[u8]) -> Vec<u8> {
+    let cipher = Magma::new(key.expose_secret());
+    let (mut res, tag) = encrypt_mgm(&cipher, nonce, aad, data, 8);
+    res.extend_from_slice(&tag);
+    res
+}
+
+//расшифрование Магма-MGM: на входе шифртекст || тэг 8 байт
+pub fn decrypt_magma_mgm(data: &[u8], key: &SecretKey<32>, nonce: &[u8; 8], aad: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 8 {
+        return None;
+    }
+    let cipher = Magma::new(key.expose_secret());
+    let (ciphertext, tag) = data.split_at(data.len() - 8);
+    decrypt_mgm(&cipher, nonce, aad, ciphertext, tag)
+}
+
+//контрольные примеры из приложения A RFC 9058
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mgm_kuznyechik_vector() {
+        let key: [u8; 32] = hex::decode("8899aabbccddeeff0011223344556677fedcba98765432100123456789abcdef")
+            .unwrap().try_into().unwrap();
+        let nonce: [u8; 16] = hex::decode("1122334455667700ffeeddccbbaa9988").unwrap().try_into().unwrap();
+        let aad = hex::decode("0202020202020202010101010101010104040404040404040303030303030303ea0505050505050505").unwrap();
+        let p = hex::decode("1122334455667700ffeeddccbbaa998800112233445566778899aabbcceeff0a112233445566778899aabbcceeff0a002233445566778899aabbcceeff0a0011aabbcc").unwrap();
+        let cipher = Kuznyechik::new(&key);
+        let (ciphertext, tag) = encrypt_mgm(&cipher, &nonce, &aad, &p, 16);
+        assert_eq!(hex::encode(&ciphertext), "a9757b8147956e9055b8a33de89f42fc8075d2212bf9fd5bd3f7069aadc16b39497ab15915a6ba85936b5d0ea9f6851cc60c14d4d3f883d0ab94420695c76deb2c7552");
+        assert_eq!(hex::encode(&tag), "cf5d656f40c34f5c46e8bb0e29fcdb4c");
+        assert_eq!(decrypt_mgm(&cipher, &nonce, &aad, &ciphertext, &tag).unwrap(), p);
+    }
+
+    #[test]
+    fn test_mgm_magma_vector() {
+        let key: [u8; 32] = hex::decode("ffeeddccbbaa99887766554433221100f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff")
+            .unwrap().try_into().unwrap();
+        let nonce: [u8; 8] = hex::decode("12def06b3c130a59").unwrap().try_into().unwrap();
+        let aad = hex::decode("01010101010101010202020202020202030303030303030304040404040404040505050505050505ea").unwrap();
+        let p = hex::decode("ffeeddccbbaa998811223344556677008899aabbcceeff0a001122334455667799aabbcceeff0a001122334455667788aabbcceeff0a00112233445566778899aabbcc").unwrap();
+        let cipher = Magma::new(&key);
+        let (ciphertext, tag) = encrypt_mgm(&cipher, &nonce, &aad, &p, 8);
+        assert_eq!(hex::encode(&ciphertext), "c795066c5f9ea03b85113342459185ae1f2e00d6bf2b785d940470b8bb9c8e7d9a5dd3731f7ddc70ec27cb0ace6fa57670f65c646abb75d547aa37c3bcb5c34e03bb9c");
+        assert_eq!(hex::encode(&tag), "a7928069aa10fd10");
+        assert_eq!(decrypt_mgm(&cipher, &nonce, &aad, &ciphertext, &tag).unwrap(), p);
+    }
+
+    #[test]
+    fn test_mgm_nonce_validation_and_tamper() {
+        let key = SecretKey::from([0x42u8; 32]);
+        let mut nonce = [0x11u8; 16];
+        let ciphertext = encrypt_kuznyechik_mgm(b"open data", &key, &nonce, b"hdr");
+        assert_eq!(decrypt_kuznyechik_mgm(&ciphertext, &key, &nonce, b"hdr").unwrap(), b"open data");
+        assert!(decrypt_kuznyechik_mgm(&ciphertext, &key, &nonce, b"hdR").is_none());
+        nonce[0] |= 0x80;
+        assert!(!is_valid_mgm_nonce(&nonce));
+        assert!(decrypt_kuznyechik_mgm(&ciphertext, &key, &nonce, b"hdr").is_none());
+
+        let nonce = [0x22u8; 8];
+        let ciphertext = encrypt_magma_mgm(b"open data", &key, &nonce, b"");
+        assert_eq!(ciphertext.len(), 9 + 8);
+        assert_eq!(decrypt_magma_mgm(&ciphertext, &key, &nonce, b"").unwrap(), b"open data");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mgm_rejects_nonce_with_top_bit() {
+        let cipher = Magma::new(&[0u8; 32]);
+        encrypt_mgm(&cipher, &[0x80u8; 8], b"", b"data", 8);
+    }
+}
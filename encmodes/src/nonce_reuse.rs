@@ -0,0 +1,192 @@
+//Анализ повторного использования nonce в CTR и OFB (many-time pad)
+//При одинаковых ключе и iv гамма одна и та же, поэтому C1 ^ C2 = P1 ^ P2 - ключ не нужен вовсе.
+//Гамма восстанавливается по столбцам: для каждой позиции выбирается байт, при котором все шифртексты
+//в этой позиции дают самый "похожий на язык" текст; дальше результат уточняется угадыванием слов (crib dragging)
+//Русский текст в UTF-8 двухбайтовый: 0xD0/0xD1 и второй байт буквы оцениваются отдельно
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Russian,
+}
+
+//частоты букв английского языка, a..z (в процентах)
+const ENGLISH_FREQ: [f64; 26] = [
+    8.2, 1.5, 2.8, 4.3, 12.7, 2.2, 2.0, 6.1, 7.0, 0.15, 0.77, 4.0, 2.4,
+    6.7, 7.5, 1.9, 0.095, 6.0, 6.3, 9.1, 2.8, 0.98, 2.4, 0.15, 2.0, 0.074,
+];
+
+//частоты букв русского языка, а..я без ё (в процентах)
+const RUSSIAN_FREQ: [f64; 32] = [
+    8.0, 1.6, 4.5, 1.7, 3.0, 8.5, 0.9, 1.6, 7.4, 1.2, 3.5, 4.4, 3.2, 6.7, 11.0, 2.8,
+    4.7, 5.5, 6.3, 2.6, 0.3, 1.0, 0.5, 1.4, 0.7, 0.4, 0.04, 1.9, 1.7, 0.3, 0.6, 2.0,
+];
+
+//оценка байта открытого текста: чем выше, тем правдоподобнее
+fn score_byte(b: u8, lang: Language) -> f64 {
+    match b {
+        b' ' => 13.0,
+        b'a'..=b'z' => match lang {
+            Language::English => ENGLISH_FREQ[(b - b'a') as usize],
+            Language::Russian => 0.5,
+        },
+        b'A'..=b'Z' => match lang {
+            Language::English => ENGLISH_FREQ[(b - b'A') as usize] / 4.0,
+            Language::Russian => 0.5,
+        },
+        b'.' | b',' | b'\'' | b'-' | b'!' | b'?' | b'\n' => 1.0,
+        b'0'..=b'9' | b':' | b';' | b'"' | b'(' | b')' => 0.3,
+        //первый байт двухбайтовой кириллицы
+        0xD0 | 0xD1 if lang == Language::Russian => 9.0,
+        //второй байт: а..п - D0 B0..BF, р..я - D1 80..8F, заглавные - D0 90..AF
+        0xB0..=0xBF if lang == Language::Russian => RUSSIAN_FREQ[(b - 0xB0) as usize],
+        0x80..=0x8F if lang == Language::Russian => RUSSIAN_FREQ[(b - 0x80) as usize + 16],
+        0x90..=0xAF if lang == Language::Russian => 0.5,
+        _ if (0x20..=0x7E).contains(&b) => 0.0,
+        _ => -10.0,
+    }
+}
+
+//оценка целого фрагмента
+pub fn score_text(text: &[u8], lang: Language) -> f64 {
+    text.iter().map(|&b| score_byte(b, lang)).sum()
+}
+
+//то, что утекает без всякого анализа: XOR двух открытых текстов
+pub fn xor_ciphertexts(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b.iter()).map(|(x, y)| x ^ y).collect()
+}
+
+//статистическое восстановление гаммы по столбцам; длина - по самому длинному шифртексту
+pub fn recover_keystream(ciphertexts: &[Vec<u8>], lang: Language) -> Vec<u8> {
+    let len = ciphertexts.iter().map(|c| c.len()).max().unwrap_or(0);
+    (0..len)
+        .map(|pos| {
+            let column: Vec<u8> = ciphertexts.iter().filter_map(|c| c.get(pos).copied()).collect();
+            (0..=255u8)
+                .max_by(|&a, &b| {
+                    let score_a: f64 = column.iter().map(|&c| score_byte(c ^ a, lang)).sum();
+                    let score_b: f64 = column.iter().map(|&c| score_byte(c ^ b, lang)).sum();
+                    score_a.total_cmp(&score_b)
+                })
+                .unwrap()
+        })
+        .collect()
+}
+
+//crib dragging: предполагаемый фрагмент crib одного текста прикладывается ко всем позициям P1 ^ P2;
+//на верной позиции получается фрагмент второго текста. Возвращает (позиция, фрагмент), лучшие первыми
+pub fn crib_drag(c1: &[u8], c2: &[u8], crib: &[u8], lang: Language) -> Vec<(usize, Vec<u8>)> {
+    let xored = xor_ciphertexts(c1, c2);
+    if crib.len() > xored.len() {
+        return Vec::new();
+    }
+    let mut res: Vec<(usize, Vec<u8>)> = (0..=xored.len() - crib.len())
+        .map(|pos| (pos, xor_ciphertexts(&xored[pos..], crib)))
+        .filter(|(_, fragment)| fragment.iter().all(|&b| score_byte(b, lang) >= 0.0))
+        .collect();
+    res.sort_by(|a, b| score_text(&b.1, lang).total_cmp(&score_text(&a.1, lang)));
+    res
+}
+
+//уточнение гаммы: известно, что в ciphertext с позиции pos стоит crib
+pub fn apply_crib(keystream: &mut [u8], ciphertext: &[u8], pos: usize, crib: &[u8]) {
+    for (i, &b) in crib.iter().enumerate() {
+        keystream[pos + i] = ciphertext[pos + i] ^ b;
+    }
+}
+
+//расшифрование восстановленной гаммой (для CTR и OFB одинаково)
+pub fn decrypt_with_keystream(keystream: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    xor_ciphertexts(keystream, ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGLISH: [&str; 16] = [
+        "I have met them at close of day",
+        "Coming with vivid faces",
+        "From counter or desk among grey",
+        "Eighteenth-century houses.",
+        "I have passed with a nod of the head",
+        "Or polite meaningless words,",
+        "Or have lingered awhile and said",
+        "Polite meaningless words,",
+        "And thought before I had done",
+        "Of a mocking tale or a gibe",
+        "To please a companion",
+        "Around the fire at the club,",
+        "Being certain that they and I",
+        "But lived where motley is worn:",
+        "All changed, changed utterly:",
+        "A terrible beauty is born.",
+    ];
+
+    const RUSSIAN: [&str; 16] = [
+        "Стояла зима, дул ветер из степи",
+        "И холодно было младенцу в вертепе",
+        "Его согревало дыханье вола",
+        "Домашние звери стояли в пещере",
+        "Над яслями теплая дымка плыла",
+        "Доху отряхнув от постельной трухи",
+        "Смотрели с утеса спросонья в даль",
+        "Вдали было поле в снегу и погост",
+        "Ограды, надгробья, оглобля в сугробе",
+        "И небо над кладбищем, полное звезд",
+        "А рядом, неведомая перед тем",
+        "Мерцала звезда по пути в Вифлеем",
+        "Она пламенела, как стог, в стороне",
+        "Как отблеск поджога, как хутор в огне",
+        "Она возвышалась горящей скирдой",
+        "Растущее зарево рдело над ней",
+    ];
+
+    //доля верно восстановленных байт гаммы там, где шифртекстов достаточно для статистики
+    fn accuracy(keystream: &[u8], ciphertexts: &[Vec<u8>], plaintexts: &[&str]) -> f64 {
+        let mut total = 0;
+        let mut correct = 0;
+        for pos in 0..keystream.len() {
+            let covering: Vec<usize> = (0..plaintexts.len()).filter(|&i| plaintexts[i].len() > pos).collect();
+            if covering.len() < 10 {
+                continue;
+            }
+            let i = covering[0];
+            total += 1;
+            if ciphertexts[i][pos] ^ keystream[pos] == plaintexts[i].as_bytes()[pos] {
+                correct += 1;
+            }
+        }
+        correct as f64 / total as f64
+    }
+
+    #[test]
+    fn test_ctr_nonce_reuse_english() {
+        let key = crate::generate_key();
+        let iv = crate::generate_iv_ctr();
+        let ciphertexts: Vec<Vec<u8>> = ENGLISH.iter().map(|p| crate::encrypt_aes192_ctr(p.as_bytes(), &key, &iv)).collect();
+        //утечка без анализа: XOR шифртекстов равен XOR открытых текстов
+        assert_eq!(xor_ciphertexts(&ciphertexts[0], &ciphertexts[1])[..10],
+                   xor_ciphertexts(ENGLISH[0].as_bytes(), ENGLISH[1].as_bytes())[..10]);
+        let mut keystream = recover_keystream(&ciphertexts, Language::English);
+        assert!(accuracy(&keystream, &ciphertexts, &ENGLISH) > 0.75);
+
+        //угаданное слово в одном тексте открывает эти позиции во всех остальных
+        let candidates = crib_drag(&ciphertexts[4], &ciphertexts[6], b" with a nod of ", Language::English);
+        assert!(candidates.iter().take(3).any(|(pos, _)| *pos == 13));
+        //известный целиком текст даёт гамму на его длину
+        apply_crib(&mut keystream, &ciphertexts[4], 0, ENGLISH[4].as_bytes());
+        let recovered = decrypt_with_keystream(&keystream, &ciphertexts[6]);
+        assert_eq!(&recovered[..ENGLISH[6].len()], ENGLISH[6].as_bytes());
+    }
+
+    #[test]
+    fn test_ofb_nonce_reuse_russian() {
+        let key = crate::generate_key();
+        let iv = crate::generate_iv();
+        let ciphertexts: Vec<Vec<u8>> = RUSSIAN.iter().map(|p| crate::encrypt_aes192_ofb(p.as_bytes(), &key, &iv)).collect();
+        let keystream = recover_keystream(&ciphertexts, Language::Russian);
+        assert!(accuracy(&keystream, &ciphertexts, &RUSSIAN) > 0.75);
+    }
+}
@@ -0,0 +1,227 @@
+//Режим OCB3 (RFC 7253) - однопроходное аутентифицированное шифрование
+//Смещения L_i получаются удвоением в GF(2^128) - тем же, что выработка подключей OMAC (double_block)
+//nonce от 1 до 15 байт, длина тэга от 1 до 16 байт
+use crate::mac::double_block;
+use crate::{BlockCipher, SecretKey, ct_eq};
+use aes::Aes192;
+use cipher::KeyInit;
+
+fn xor_block(a: &[u8; 16], b: &[u8; 16]) -> [u8; 16] {
+    let mut res = [0u8; 16];
+    for i in 0..16 {
+        res[i] = a[i] ^ b[i];
+    }
+    res
+}
+
+//предвычисленные смещения L_*, L_$ и L_i = 2^i * L_$ * 2
+struct Offsets {
+    l_star: [u8; 16],
+    l_dollar: [u8; 16],
+    l: Vec<[u8; 16]>,
+}
+
+impl Offsets {
+    fn new<C: BlockCipher<16>>(cipher: &C, blocks: usize) -> Self {
+        let l_star = cipher.encrypt(&[0u8; 16]);
+        let l_dollar = double_block(&l_star);
+        let mut l = vec![double_block(&l_dollar)];
+        //для номеров блоков до blocks нужно L_i с i <= log2(blocks)
+        while (1usize << l.len()) <= blocks {
+            let next = double_block(l.last().unwrap());
+            l.push(next);
+        }
+        Offsets { l_star, l_dollar, l }
+    }
+
+    //L_{ntz(i)} для блока с номером i (с единицы)
+    fn for_block(&self, i: usize) -> &[u8; 16] {
+        &self.l[i.trailing_zeros() as usize]
+    }
+}
+
+//HASH(K, A) - имитовставка ассоциированных данных
+fn ocb_hash<C: BlockCipher<16>>(cipher: &C, offsets: &Offsets, aad: &[u8]) -> [u8; 16] {
+    let mut sum = [0u8; 16];
+    let mut offset = [0u8; 16];
+    let full = aad.len() / 16;
+    for (i, block) in aad.chunks(16).take(full).enumerate() {
+        offset = xor_block(&offset, offsets.for_block(i + 1));
+        sum = xor_block(&sum, &cipher.encrypt(&xor_block(block.try_into().unwrap(), &offset)));
+    }
+    let tail = &aad[full * 16..];
+    if !tail.is_empty() {
+        offset = xor_block(&offset, &offsets.l_star);
+        let mut padded = [0u8; 16];
+        padded[..tail.len()].copy_from_slice(tail);
+        padded[tail.len()] = 0x80;
+        sum = xor_block(&sum, &cipher.encrypt(&xor_block(&padded, &offset)));
+    }
+    sum
+}
+
+//начальное смещение из nonce: Offset_0 = Stretch[1 + bottom .. 128 + bottom]
+fn initial_offset<C: BlockCipher<16>>(cipher: &C, nonce: &[u8], tag_len: usize) -> [u8; 16] {
+    let mut nonce_block = [0u8; 16];
+    nonce_block[0] = (((tag_len * 8) % 128) as u8) << 1;
+    nonce_block[16 - nonce.len()..].copy_from_slice(nonce);
+    nonce_block[15 - nonce.len()] |= 1;
+    let bottom = (nonce_block[15] & 0x3f) as u32;
+    nonce_block[15] &= 0xc0;
+    let ktop = cipher.encrypt(&nonce_block);
+    let mut stretch = [0u8; 24];
+    stretch[..16].copy_from_slice(&ktop);
+    for i in 0..8 {
+        stretch[16 + i] = ktop[i] ^ ktop[i + 1];
+    }
+    //сдвиг 192-битной строки влево на bottom бит и взятие старших 128 бит
+    let hi = u128::from_be_bytes(stretch[..16].try_into().unwrap());
+    let lo = u64::from_be_bytes(stretch[16..].try_into().unwrap()) as u128;
+    let offset = if bottom == 0 { hi } else { (hi << bottom) | (lo >> (64 - bottom)) };
+    offset.to_be_bytes()
+}
+
+fn check_params(nonce_len: usize, tag_len: usize) -> bool {
+    (1..=15).contains(&nonce_len) && (1..=16).contains(&tag_len)
+}
+
+//основной проход: шифрует или расшифровывает данные и возвращает (результат, полный тэг)
+fn ocb_process<C: BlockCipher<16>>(cipher: &C, nonce: &[u8], aad: &[u8], data: &[u8], tag_len: usize, encrypt: bool) -> (Vec<u8>, [u8; 16]) {
+    let full = data.len() / 16;
+    let offsets = Offsets::new(cipher, full.max(aad.len() / 16));
+    let mut offset = initial_offset(cipher, nonce, tag_len);
+    let mut checksum = [0u8; 16];
+    let mut res = Vec::with_capacity(data.len());
+    for (i, block) in data.chunks(16).take(full).enumerate() {
+        offset = xor_block(&offset, offsets.for_block(i + 1));
+        let input = xor_block(block.try_into().unwrap(), &offset);
+        let output = if encrypt { cipher.encrypt(&input) } else { cipher.decrypt(&input) };
+        let out_block = xor_block(&output, &offset);
+        let plain_block = if encrypt { block.try_into().unwrap() } else { out_block };
+        checksum = xor_block(&checksum, &plain_block);
+        res.extend_from_slice(&out_block);
+    }
+    let tail = &data[full * 16..];
+    if !tail.is_empty() {
+        offset = xor_block(&offset, &offsets.l_star);
+        let pad = cipher.encrypt(&offset);
+        let out_tail: Vec<u8> = tail.iter().zip(pad.iter()).map(|(a, b)| a ^ b).collect();
+        let plain_tail = if encrypt { tail } else { &out_tail[..] };
+        let mut padded = [0u8; 16];
+        padded[..plain_tail.len()].copy_from_slice(plain_tail);
+        padded[plain_tail.len()] = 0x80;
+        checksum = xor_block(&checksum, &padded);
+        res.extend_from_slice(&out_tail);
+    }
+    let tag = xor_block(
+        &cipher.encrypt(&xor_block(&xor_block(&checksum, &offset), &offsets.l_dollar)),
+        &ocb_hash(cipher, &offsets, aad),
+    );
+    (res, tag)
+}
+
+//шифрование OCB3, возвращает (шифртекст, тэг длины tag_len байт)
+pub fn encrypt_ocb<C: BlockCipher<16>>(
+    cipher: &C,
+    nonce: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    tag_len: usize,
+) -> (Vec<u8>, Vec<u8>) {
+    assert!(check_params(nonce.len(), tag_len), "nonce OCB - от 1 до 15 байт, тэг - от 1 до 16 байт");
+    let (ciphertext, tag) = ocb_process(cipher, nonce, aad, data, tag_len, true);
+    (ciphertext, tag[..tag_len].to_vec())
+}
+
+//расшифрование OCB3, при несовпадении тэга возвращает None
+pub fn decrypt_ocb<C: BlockCipher<16>>(
+    cipher: &C,
+    nonce: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    tag: &[u8],
+) -> Option<Vec<u8>> {
+    if !check_params(nonce.len(), tag.len()) {
+        return None;
+    }
+    let (plaintext, expected) = ocb_process(cipher, nonce, aad, data, tag.len(), false);
+    if !ct_eq(&expected[..tag.len()], tag) {
+        return None;
+    }
+    Some(plaintext)
+}
+
+//шифрование AES192-OCB3 с nonce 12 байт: на выходе шифртекст || тэг 16 байт
+pub fn encrypt_aes192_ocb(data: &[u8], key: &SecretKey<24>, nonce: &[u8; 12], aad: &[u8]) -> Vec<u8> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    let (mut res, tag) = encrypt_ocb(&cipher, nonce, aad, data, 16);
+    res.extend_from_slice(&tag);
+    res
+}
+
+//расшифрование AES192-OCB3: на входе шифртекст || тэг 16 байт
+pub fn decrypt_aes192_ocb(data: &[u8], key: &SecretKey<24>, nonce: &[u8; 12], aad: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 16 {
+        return None;
+    }
+    let cipher = Aes192::new(key.expose_secret().into());
+    let (ciphertext, tag) = data.split_at(data.len() - 16);
+    decrypt_ocb(&cipher, nonce, aad, ciphertext, tag)
+}
+
+//примеры из приложения A RFC 7253
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::Aes128;
+
+    fn check(cipher: &Aes128, nonce: &str, aad: &str, p: &str, tag_len: usize, result: &str) {
+        let nonce = hex::decode(nonce).unwrap();
+        let aad = hex::decode(aad).unwrap();
+        let p = hex::decode(p).unwrap();
+        let (mut c, tag) = encrypt_ocb(cipher, &nonce, &aad, &p, tag_len);
+        assert_eq!(decrypt_ocb(cipher, &nonce, &aad, &c, &tag).unwrap(), p);
+        c.extend_from_slice(&tag);
+        assert_eq!(hex::encode(&c), result);
+    }
+
+    #[test]
+    fn test_ocb_rfc7253_vectors() {
+        let key: [u8; 16] = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap().try_into().unwrap();
+        let cipher = Aes128::new(&key.into());
+        let a8 = "0001020304050607";
+        let a16 = "000102030405060708090a0b0c0d0e0f";
+        let a24 = "000102030405060708090a0b0c0d0e0f1011121314151617";
+        check(&cipher, "bbaa99887766554433221100", "", "", 16, "785407bfffc8ad9edcc5520ac9111ee6");
+        check(&cipher, "bbaa99887766554433221101", a8, a8, 16, "6820b3657b6f615a5725bda0d3b4eb3a257c9af1f8f03009");
+        check(&cipher, "bbaa99887766554433221102", a8, "", 16, "81017f8203f081277152fade694a0a00");
+        check(&cipher, "bbaa99887766554433221103", "", a8, 16, "45dd69f8f5aae72414054cd1f35d82760b2cd00d2f99bfa9");
+        check(&cipher, "bbaa99887766554433221104", a16, a16, 16, "571d535b60b277188be5147170a9a22c3ad7a4ff3835b8c5701c1ccec8fc3358");
+        check(&cipher, "bbaa99887766554433221105", a16, "", 16, "8cf761b6902ef764462ad86498ca6b97");
+        check(&cipher, "bbaa99887766554433221106", "", a16, 16, "5ce88ec2e0692706a915c00aeb8b2396f40e1c743f52436bdf06d8fa1eca343d");
+        check(&cipher, "bbaa99887766554433221107", a24, a24, 16,
+              "1ca2207308c87c010756104d8840ce1952f09673a448a122c92c62241051f57356d7f3c90bb0e07f");
+    }
+
+    #[test]
+    fn test_ocb_rfc7253_short_tag_vector() {
+        let key: [u8; 16] = hex::decode("0f0e0d0c0b0a09080706050403020100").unwrap().try_into().unwrap();
+        let cipher = Aes128::new(&key.into());
+        let a40 = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f2021222324252627";
+        check(&cipher, "bbaa9988776655443322110d", a40, a40, 12,
+              "1792a4e31e0755fb03e31b22116e6c2ddf9efd6e33d536f1a0124b0a55bae884ed93481529c76b6ad0c515f4d1cdd4fdac4f02aa");
+    }
+
+    #[test]
+    fn test_aes192_ocb_round_trip_and_tamper() {
+        let key = crate::generate_key();
+        let nonce = [3u8; 12];
+        let message = vec![0x61u8; 100];
+        let c = encrypt_aes192_ocb(&message, &key, &nonce, b"aad");
+        assert_eq!(decrypt_aes192_ocb(&c, &key, &nonce, b"aad").unwrap(), message);
+        let mut broken = c.clone();
+        broken[50] ^= 4;
+        assert!(decrypt_aes192_ocb(&broken, &key, &nonce, b"aad").is_none());
+        assert!(decrypt_aes192_ocb(&c, &key, &[4u8; 12], b"aad").is_none());
+    }
+}
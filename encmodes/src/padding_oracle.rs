@@ -0,0 +1,131 @@
+//Атака Воденэ на оракул дополнения (padding oracle) против CBC с PKCS7
+//Оракул сообщает только одно - корректно ли дополнение после расшифрования (iv, шифртекст).
+//Для блока C_i подбирается фальшивый предыдущий блок R: если R || C_i расшифровывается с дополнением
+//0x01, то D(C_i)[15] = R[15] ^ 0x01, и так байт за байтом; затем P_i = D(C_i) ^ C_{i-1}.
+//На блок уходит не больше 16 * 256 запросов, ключ атакующему не нужен.
+//Защита - encrypt-then-MAC: имитовставка проверяется до снятия дополнения, и оракул всегда отвечает "нет"
+use crate::pbe::imito_hmac;
+use crate::{ct_eq, decrypt_aes192_cbc, encrypt_aes192_cbc, pkcs7_unpad, CbcCipher, SecretKey};
+
+//оракул поверх decrypt_aes192_cbc: сервер расшифровывает и по-разному реагирует на плохое дополнение
+pub fn aes192_cbc_padding_oracle(key: &SecretKey<24>) -> impl Fn(&[u8; 16], &[u8]) -> bool {
+    //ключ расширяется один раз: атака делает тысячи запросов
+    let cipher = CbcCipher::new(key);
+    move |iv, ciphertext| pkcs7_unpad(&cipher.decrypt(ciphertext, iv), 16).is_some()
+}
+
+//восстановление промежуточного значения D(C) для одного блока
+fn recover_intermediate<F: Fn(&[u8; 16], &[u8]) -> bool>(oracle: &F, block: &[u8; 16]) -> Option<[u8; 16]> {
+    let mut intermediate = [0u8; 16];
+    for pad in 1..=16u8 {
+        let pos = 16 - pad as usize;
+        let mut r = [0u8; 16];
+        //уже найденные байты выставляются так, чтобы давать значение pad
+        for j in pos + 1..16 {
+            r[j] = intermediate[j] ^ pad;
+        }
+        let mut found = None;
+        for guess in 0..=255u8 {
+            r[pos] = guess;
+            if !oracle(&r, block) {
+                continue;
+            }
+            //для последнего байта корректным может оказаться и более длинное дополнение (02 02 ...),
+            //поэтому меняем предыдущий байт и переспрашиваем
+            if pad == 1 {
+                let mut check = r;
+                check[pos - 1] ^= 0xff;
+                if !oracle(&check, block) {
+                    continue;
+                }
+            }
+            found = Some(guess);
+            break;
+        }
+        intermediate[pos] = found? ^ pad;
+    }
+    Some(intermediate)
+}
+
+//расшифрование шифртекста CBC через оракул; None, если оракул ни разу не подтвердил дополнение
+pub fn padding_oracle_attack<F: Fn(&[u8; 16], &[u8]) -> bool>(oracle: F, iv: &[u8; 16], ciphertext: &[u8]) -> Option<Vec<u8>> {
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(16) {
+        return None;
+    }
+    let mut res = Vec::with_capacity(ciphertext.len());
+    let mut prev = *iv;
+    for block in ciphertext.chunks(16) {
+        let block: [u8; 16] = block.try_into().unwrap();
+        let intermediate = recover_intermediate(&oracle, &block)?;
+        res.extend(intermediate.iter().zip(prev.iter()).map(|(a, b)| a ^ b));
+        prev = block;
+    }
+    pkcs7_unpad(&res, 16)
+}
+
+//защита: шифртекст CBC || HMAC(iv || шифртекст) на отдельном ключе
+pub fn encrypt_aes192_cbc_then_mac(data: &[u8], key: &SecretKey<24>, mac_key: &SecretKey<24>, iv: &[u8; 16]) -> Vec<u8> {
+    let mut res = encrypt_aes192_cbc(data, key, iv);
+    let mut mac_input = iv.to_vec();
+    mac_input.extend_from_slice(&res);
+    res.extend_from_slice(&imito_hmac(mac_key, &mac_input));
+    res
+}
+
+//сначала проверяется имитовставка, и только потом расшифрование и дополнение
+pub fn decrypt_aes192_cbc_then_mac(data: &[u8], key: &SecretKey<24>, mac_key: &SecretKey<24>, iv: &[u8; 16]) -> Option<Vec<u8>> {
+    if data.len() < 32 + 16 {
+        return None;
+    }
+    let (ciphertext, tag) = data.split_at(data.len() - 32);
+    let mut mac_input = iv.to_vec();
+    mac_input.extend_from_slice(ciphertext);
+    if !ct_eq(&imito_hmac(mac_key, &mac_input), tag) || !ciphertext.len().is_multiple_of(16) {
+        return None;
+    }
+    pkcs7_unpad(&decrypt_aes192_cbc(ciphertext, key, iv), 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_padding_oracle_recovers_random_message() {
+        let key = crate::generate_key();
+        let iv = crate::generate_iv();
+        let mut rng = rand::rng();
+        for len in [0, 1, 15, 16, 47] {
+            let message: Vec<u8> = (0..len).map(|_| rng.random()).collect();
+            let ciphertext = encrypt_aes192_cbc(&message, &key, &iv);
+            let queries = Cell::new(0);
+            let oracle = aes192_cbc_padding_oracle(&key);
+            let recovered = padding_oracle_attack(|iv, c| {
+                queries.set(queries.get() + 1);
+                oracle(iv, c)
+            }, &iv, &ciphertext);
+            assert_eq!(recovered.unwrap(), message);
+            assert!(queries.get() <= ciphertext.len() / 16 * (16 * 256 + 1));
+        }
+    }
+
+    #[test]
+    fn test_encrypt_then_mac_defeats_oracle() {
+        let key = crate::generate_key();
+        let mac_key = crate::generate_key();
+        let iv = crate::generate_iv();
+        let message = b"attack at dawn, bring the codebook";
+        let protected = encrypt_aes192_cbc_then_mac(message, &key, &mac_key, &iv);
+        assert_eq!(decrypt_aes192_cbc_then_mac(&protected, &key, &mac_key, &iv).unwrap(), message);
+        //атакующий подставляет свои (iv, блок) и исходный тэг - оракул теперь всегда отвечает "нет"
+        let (ciphertext, tag) = protected.split_at(protected.len() - 32);
+        let oracle = |iv: &[u8; 16], c: &[u8]| {
+            let mut data = c.to_vec();
+            data.extend_from_slice(tag);
+            decrypt_aes192_cbc_then_mac(&data, &key, &mac_key, iv).is_some()
+        };
+        assert!(padding_oracle_attack(oracle, &iv, ciphertext).is_none());
+    }
+}
@@ -0,0 +1,154 @@
+//Параллельные ECB, CTR и расшифрование CBC для больших буферов
+//Блоки этих режимов обрабатываются независимо, поэтому данные делятся на куски по числу потоков (std::thread::scope),
+//а внутри потока блоки шифруются пачками через encrypt_blocks - так AES-NI обрабатывает несколько блоков одновременно
+//Результат побайтно совпадает с последовательными encrypt_aes192_* (включая дополнение PKCS7)
+use crate::{generate_nc, pkcs7_pad, SecretKey};
+use aes::{Aes192, Block};
+use cipher::{BlockCipherDecrypt, BlockCipherEncrypt, KeyInit};
+
+//сколько блоков шифруется за один вызов encrypt_blocks
+const BATCH_BLOCKS: usize = 64;
+//меньше этого числа блоков на поток запускать потоки невыгодно
+const MIN_BLOCKS_PER_THREAD: usize = 1024;
+
+//число потоков по умолчанию - число доступных ядер
+pub fn default_threads() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+//делит buf на куски из целых блоков и вызывает f(номер первого блока куска, кусок) в отдельных потоках
+fn for_each_chunk<F: Fn(usize, &mut [u8]) + Sync>(buf: &mut [u8], threads: usize, f: F) {
+    let blocks = buf.len().div_ceil(16);
+    let blocks_per_thread = blocks.div_ceil(threads.max(1)).max(MIN_BLOCKS_PER_THREAD);
+    let chunk_len = blocks_per_thread * 16;
+    std::thread::scope(|s| {
+        for (i, chunk) in buf.chunks_mut(chunk_len).enumerate() {
+            let f = &f;
+            s.spawn(move || f(i * blocks_per_thread, chunk));
+        }
+    });
+}
+
+//шифрование блоков куска на месте пачками по BATCH_BLOCKS
+fn process_blocks(cipher: &Aes192, chunk: &mut [u8], encrypt: bool) {
+    for batch in chunk.chunks_mut(16 * BATCH_BLOCKS) {
+        let mut blocks: Vec<Block> = batch.chunks(16).map(|b| Block::try_from(b).unwrap()).collect();
+        if encrypt {
+            cipher.encrypt_blocks(&mut blocks);
+        } else {
+            cipher.decrypt_blocks(&mut blocks);
+        }
+        for (out, block) in batch.chunks_mut(16).zip(blocks.iter()) {
+            out.copy_from_slice(block);
+        }
+    }
+}
+
+//наложение гаммы CTR на кусок, начинающийся с блока first_block
+fn ctr_keystream(cipher: &Aes192, iv: &[u8; 8], first_block: usize, chunk: &mut [u8]) {
+    for (j, batch) in chunk.chunks_mut(16 * BATCH_BLOCKS).enumerate() {
+        let first = (first_block + j * BATCH_BLOCKS) as u64;
+        let mut blocks: Vec<Block> = (0..batch.len().div_ceil(16) as u64)
+            .map(|i| Block::from(generate_nc(*iv, first + i)))
+            .collect();
+        cipher.encrypt_blocks(&mut blocks);
+        for (out, gamma) in batch.chunks_mut(16).zip(blocks.iter()) {
+            for (b, g) in out.iter_mut().zip(gamma.iter()) {
+                *b ^= g;
+            }
+        }
+    }
+}
+
+//параллельное шифрование ECB, совпадает с encrypt_aes192_ecb
+pub fn par_encrypt_aes192_ecb(data: &[u8], key: &SecretKey<24>, threads: usize) -> Vec<u8> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    let mut res = pkcs7_pad(data, 16);
+    for_each_chunk(&mut res, threads, |_, chunk| process_blocks(&cipher, chunk, true));
+    res
+}
+
+//параллельное расшифрование ECB, совпадает с decrypt_aes192_ecb (дополнение не снимается)
+//None, если шифртекст не из целых блоков
+pub fn par_decrypt_aes192_ecb(data: &[u8], key: &SecretKey<24>, threads: usize) -> Option<Vec<u8>> {
+    if !data.len().is_multiple_of(16) {
+        return None;
+    }
+    let cipher = Aes192::new(key.expose_secret().into());
+    let mut res = data.to_vec();
+    for_each_chunk(&mut res, threads, |_, chunk| process_blocks(&cipher, chunk, false));
+    Some(res)
+}
+
+//параллельное шифрование CTR, совпадает с encrypt_aes192_ctr
+pub fn par_encrypt_aes192_ctr(data: &[u8], key: &SecretKey<24>, iv: &[u8; 8], threads: usize) -> Vec<u8> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    let mut res = pkcs7_pad(data, 16);
+    for_each_chunk(&mut res, threads, |first, chunk| ctr_keystream(&cipher, iv, first, chunk));
+    res
+}
+
+//параллельное расшифрование CTR, совпадает с decrypt_aes192_ctr
+pub fn par_decrypt_aes192_ctr(data: &[u8], key: &SecretKey<24>, iv: &[u8; 8], threads: usize) -> Vec<u8> {
+    let cipher = Aes192::new(key.expose_secret().into());
+    let mut res = data.to_vec();
+    for_each_chunk(&mut res, threads, |first, chunk| ctr_keystream(&cipher, iv, first, chunk));
+    res
+}
+
+//параллельное расшифрование CBC: P_i = D(C_i) ^ C_{i-1}, все C известны заранее
+//None, если шифртекст не из целых блоков
+pub fn par_decrypt_aes192_cbc(data: &[u8], key: &SecretKey<24>, iv: &[u8; 16], threads: usize) -> Option<Vec<u8>> {
+    if !data.len().is_multiple_of(16) {
+        return None;
+    }
+    let cipher = Aes192::new(key.expose_secret().into());
+    let mut res = data.to_vec();
+    for_each_chunk(&mut res, threads, |first, chunk| {
+        process_blocks(&cipher, chunk, false);
+        let prev = if first == 0 { &iv[..] } else { &data[(first - 1) * 16..first * 16] };
+        let prev_blocks = prev.iter().chain(data[first * 16..].iter());
+        for (b, c) in chunk.iter_mut().zip(prev_blocks) {
+            *b ^= c;
+        }
+    });
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let key = crate::generate_key();
+        let iv = crate::generate_iv();
+        let iv_ctr = crate::generate_iv_ctr();
+        //длины вокруг границ кусков и пачек, включая неполный последний блок
+        for len in [0, 1, 15, 16, 1000, 16 * 1024 - 3, 16 * 1024 * 5 + 7] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 % 256) as u8).collect();
+            for threads in [1, 3, 8] {
+                let ctr = par_encrypt_aes192_ctr(&data, &key, &iv_ctr, threads);
+                assert_eq!(ctr, crate::encrypt_aes192_ctr(&data, &key, &iv_ctr));
+                assert_eq!(par_decrypt_aes192_ctr(&ctr, &key, &iv_ctr, threads), crate::decrypt_aes192_ctr(&ctr, &key, &iv_ctr));
+                let ecb = par_encrypt_aes192_ecb(&data, &key, threads);
+                assert_eq!(ecb, crate::encrypt_aes192_ecb(&data, &key));
+                assert_eq!(par_decrypt_aes192_ecb(&ecb, &key, threads).unwrap(), crate::decrypt_aes192_ecb(&ecb, &key));
+                let cbc = crate::encrypt_aes192_cbc(&data, &key, &iv);
+                assert_eq!(par_decrypt_aes192_cbc(&cbc, &key, &iv, threads).unwrap(), crate::decrypt_aes192_cbc(&cbc, &key, &iv));
+            }
+        }
+    }
+
+    //шифртекст не из целых блоков - None, а не паника
+    #[test]
+    fn test_parallel_decrypt_partial_block() {
+        let key = crate::generate_key();
+        let iv = crate::generate_iv();
+        for len in [1, 15, 17, 16 * 1024 + 5] {
+            let data = vec![0x5au8; len];
+            assert_eq!(par_decrypt_aes192_ecb(&data, &key, 4), None);
+            assert_eq!(par_decrypt_aes192_cbc(&data, &key, &iv, 4), None);
+        }
+    }
+}
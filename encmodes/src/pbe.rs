@@ -0,0 +1,237 @@
+//Шифрование на пароле: ключи выводятся PBKDF2-HMAC-SHA256 из пароля и случайной соли,
+//данные шифруются AES192-CBC и затем аутентифицируются HMAC (encrypt-then-MAC)
+//Формат: "ENCP" | версия (1) | итерации u32 big-endian | соль 16 байт | iv 16 байт | шифртекст | HMAC 32 байта
+//HMAC считается по всему, что перед ним, поэтому подмена числа итераций или соли тоже обнаруживается
+//pbkdf2_hmac_sha256 и imito_hmac - копии из kdf и imito (крейты независимы)
+use crate::container::{self, ContainerError, Header, KdfParams};
+use crate::{ct_eq, decrypt_aes192_cbc, encrypt_aes192_cbc, generate_iv, pkcs7_unpad, SecretKey};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+pub const MAGIC: [u8; 4] = *b"ENCP";
+pub const VERSION: u8 = 1;
+//число итераций по умолчанию (рекомендация OWASP для PBKDF2-HMAC-SHA256)
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+//верхняя граница числа итераций из заголовка: оно читается до проверки HMAC, и без границы
+//подделанный заголовок с u32::MAX заставил бы считать PBKDF2 часами
+pub const MAX_ITERATIONS: u32 = 10_000_000;
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = 4 + 1 + 4 + SALT_LEN + 16;
+const TAG_LEN: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PbeError {
+    //число итераций 0 или больше MAX_ITERATIONS
+    InvalidIterations,
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+//HMAC-SHA256 (RFC 2104): длинный ключ сначала хешируется, короткий дополняется нулями до 64 байт
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut key_plus = [0u8; 64];
+    if key.len() <= 64 {
+        key_plus[..key.len()].copy_from_slice(key);
+    } else {
+        key_plus[..32].copy_from_slice(&sha256(key));
+    }
+    let mut inner = key_plus.map(|b| b ^ 0x36).to_vec();
+    inner.extend_from_slice(data);
+    let mut outer = key_plus.map(|b| b ^ 0x5c).to_vec();
+    outer.extend_from_slice(&sha256(&inner));
+    sha256(&outer)
+}
+
+//как imito_hmac: ключ подаётся в HMAC в виде hex-строки
+pub fn imito_hmac(key: &SecretKey<24>, data: &[u8]) -> [u8; 32] {
+    let hex_key = Zeroizing::new(hex::encode(key.expose_secret()));
+    hmac_sha256(hex_key.as_bytes(), data)
+}
+
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, dk_len: usize) -> Vec<u8> {
+    let mut result = Vec::with_capacity(dk_len.div_ceil(32) * 32);
+    for i in 1..=dk_len.div_ceil(32) as u32 {
+        let mut salt_i = salt.to_vec();
+        salt_i.extend_from_slice(&i.to_be_bytes());
+        let mut u = hmac_sha256(password, &salt_i);
+        let mut t = u;
+        for _ in 1..iterations {
+            u = hmac_sha256(password, &u);
+            for (t_byte, u_byte) in t.iter_mut().zip(u.iter()) {
+                *t_byte ^= *u_byte;
+            }
+        }
+        result.extend_from_slice(&t);
+    }
+    result.truncate(dk_len);
+    result
+}
+
+//ключ шифрования и ключ HMAC из одного вывода PBKDF2
+fn derive_keys(password: &[u8], salt: &[u8], iterations: u32) -> (SecretKey<24>, SecretKey<24>) {
+    let dk = Zeroizing::new(pbkdf2_hmac_sha256(password, salt, iterations, 48));
+    (SecretKey::from_slice(&dk[..24]).unwrap(), SecretKey::from_slice(&dk[24..]).unwrap())
+}
+
+pub fn encrypt_with_password(data: &[u8], password: &[u8], iterations: u32) -> Result<Vec<u8>, PbeError> {
+    if !(1..=MAX_ITERATIONS).contains(&iterations) {
+        return Err(PbeError::InvalidIterations);
+    }
+    let mut salt = [0u8; SALT_LEN];
+    rand::rng().fill(&mut salt);
+    let iv = generate_iv();
+    let (enc_key, mac_key) = derive_keys(password, &salt, iterations);
+    let mut res = MAGIC.to_vec();
+    res.push(VERSION);
+    res.extend_from_slice(&iterations.to_be_bytes());
+    res.extend_from_slice(&salt);
+    res.extend_from_slice(&iv);
+    res.extend_from_slice(&encrypt_aes192_cbc(data, &enc_key, &iv));
+    let tag = imito_hmac(&mac_key, &res);
+    res.extend_from_slice(&tag);
+    Ok(res)
+}
+
+//расшифрование: None при неверном пароле, искажении данных или неизвестном формате
+pub fn decrypt_with_password(data: &[u8], password: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < HEADER_LEN + 16 + TAG_LEN || data[..4] != MAGIC || data[4] != VERSION {
+        return None;
+    }
+    let (body, tag) = data.split_at(data.len() - TAG_LEN);
+    let ciphertext = &body[HEADER_LEN..];
+    if !ciphertext.len().is_multiple_of(16) {
+        return None;
+    }
+    let iterations = u32::from_be_bytes(body[5..9].try_into().unwrap());
+    if iterations == 0 || iterations > MAX_ITERATIONS {
+        return None;
+    }
+    let salt = &body[9..9 + SALT_LEN];
+    let iv: [u8; 16] = body[9 + SALT_LEN..HEADER_LEN].try_into().unwrap();
+    let (enc_key, mac_key) = derive_keys(password, salt, iterations);
+    if !ct_eq(&imito_hmac(&mac_key, body), tag) {
+        return None;
+    }
+    //дополнение проверяется только после HMAC, поэтому оракула дополнения нет
+    pkcs7_unpad(&decrypt_aes192_cbc(ciphertext, &enc_key, &iv), 16)
+}
+
+//ключ контейнера из пароля по параметрам KDF из его заголовка; None, если итераций 0 или больше MAX_ITERATIONS
+pub fn container_key(password: &[u8], kdf: &KdfParams) -> Option<SecretKey<24>> {
+    match kdf {
+        KdfParams::Pbkdf2HmacSha256 { iterations, salt } if (1..=MAX_ITERATIONS).contains(iterations) => {
+            let dk = Zeroizing::new(pbkdf2_hmac_sha256(password, salt, *iterations, 24));
+            SecretKey::from_slice(&dk)
+        }
+        _ => None,
+    }
+}
+
+//шифрование в контейнер (AEAD по кускам) на пароле: соль и итерации записываются в заголовок;
+//UnsupportedParameters, если итераций 0 или больше MAX_ITERATIONS
+pub fn encrypt_container_with_password(data: &[u8], password: &[u8], iterations: u32, mut header: Header) -> Result<Vec<u8>, ContainerError> {
+    let mut salt = vec![0u8; SALT_LEN];
+    rand::rng().fill(&mut salt[..]);
+    header.kdf = KdfParams::Pbkdf2HmacSha256 { iterations, salt };
+    let key = container_key(password, &header.kdf).ok_or(ContainerError::UnsupportedParameters)?;
+    container::encrypt_aes192_container(data, &key, header)
+}
+
+pub fn decrypt_container_with_password(data: &[u8], password: &[u8]) -> Result<(Header, Vec<u8>), ContainerError> {
+    let mut input = data;
+    let header = Header::read_from(&mut input)?;
+    let key = container_key(password, &header.kdf).ok_or(ContainerError::UnsupportedParameters)?;
+    let plaintext = container::ContainerReader::new(input, &key, header.clone())?.read_to_end()?;
+    Ok((header, plaintext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::AeadMode;
+
+    //RFC 4231, тест 2
+    #[test]
+    fn test_hmac_sha256_vector() {
+        let tag = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(hex::encode(tag), "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        //imito_hmac совпадает с HMAC на ключе-строке hex
+        let tag = imito_hmac(&SecretKey::from([1u8; 24]), b"abc");
+        assert_eq!(hex::encode(tag), "a773f6ebbb4673e9e012c6315b99f205cdd85affb793058b94291f089667bdf5");
+    }
+
+    //RFC 7914, раздел 11
+    #[test]
+    fn test_pbkdf2_vector() {
+        let dk = pbkdf2_hmac_sha256(b"passwd", b"salt", 1, 64);
+        assert_eq!(hex::encode(dk), concat!(
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc",
+            "49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        ));
+    }
+
+    #[test]
+    fn test_password_round_trip_and_tamper() {
+        let message = "Мело, мело по всей земле".as_bytes();
+        let c = encrypt_with_password(message, b"correct horse", 1000).unwrap();
+        assert_eq!(decrypt_with_password(&c, b"correct horse").unwrap(), message);
+        assert!(decrypt_with_password(&c, b"wrong horse").is_none());
+        //одинаковые сообщения дают разные шифртексты из-за случайной соли и iv
+        assert_ne!(c, encrypt_with_password(message, b"correct horse", 1000).unwrap());
+        //искажение шифртекста и понижение числа итераций
+        let mut broken = c.clone();
+        broken[HEADER_LEN + 3] ^= 1;
+        assert!(decrypt_with_password(&broken, b"correct horse").is_none());
+        let mut fewer = c.clone();
+        fewer[5..9].copy_from_slice(&1u32.to_be_bytes());
+        assert!(decrypt_with_password(&fewer, b"correct horse").is_none());
+        assert!(decrypt_with_password(&c[..c.len() - 1], b"correct horse").is_none());
+    }
+
+    //подделанный заголовок с огромным числом итераций отвергается сразу, без вывода ключа
+    #[test]
+    fn test_forged_iterations_rejected() {
+        let mut forged = encrypt_with_password(b"data", b"pw", 1).unwrap();
+        forged[5..9].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(decrypt_with_password(&forged, b"pw").is_none());
+        forged[5..9].copy_from_slice(&(MAX_ITERATIONS + 1).to_be_bytes());
+        assert!(decrypt_with_password(&forged, b"pw").is_none());
+
+        let header = Header::new(AeadMode::Ocb, 1024, b"");
+        let c = encrypt_container_with_password(b"data", b"pw", 1, header).unwrap();
+        let mut input = &c[..];
+        let mut header = Header::read_from(&mut input).unwrap();
+        header.kdf = KdfParams::Pbkdf2HmacSha256 { iterations: u32::MAX, salt: vec![0; SALT_LEN] };
+        let mut forged = header.to_bytes().unwrap();
+        forged.extend_from_slice(input);
+        assert!(matches!(decrypt_container_with_password(&forged, b"pw"), Err(ContainerError::UnsupportedParameters)));
+        assert!(container_key(b"pw", &header.kdf).is_none());
+    }
+
+    //недопустимое число итераций при шифровании - ошибка, а не паника
+    #[test]
+    fn test_invalid_iterations_on_encrypt() {
+        for iterations in [0, MAX_ITERATIONS + 1] {
+            assert_eq!(encrypt_with_password(b"data", b"pw", iterations), Err(PbeError::InvalidIterations));
+            let header = Header::new(AeadMode::Ocb, 1024, b"");
+            let res = encrypt_container_with_password(b"data", b"pw", iterations, header);
+            assert!(matches!(res, Err(ContainerError::UnsupportedParameters)));
+        }
+    }
+
+    #[test]
+    fn test_container_with_password() {
+        let message = vec![7u8; 5000];
+        let header = Header::new(AeadMode::Ocb, 1024, b"");
+        let c = encrypt_container_with_password(&message, b"secret", 100, header).unwrap();
+        let (header, plaintext) = decrypt_container_with_password(&c, b"secret").unwrap();
+        assert!(matches!(header.kdf, KdfParams::Pbkdf2HmacSha256 { iterations: 100, .. }));
+        assert_eq!(plaintext, message);
+        assert!(matches!(decrypt_container_with_password(&c, b"Secret"), Err(ContainerError::Authentication)));
+    }
+}
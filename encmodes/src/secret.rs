@@ -0,0 +1,145 @@
+//Секретный ключ фиксированной длины N байт. В отличие от голого [u8; N]:
+//  - при удалении затирается нулями (zeroize - запись не выбрасывается оптимизатором),
+//  - Debug не печатает содержимое,
+//  - не копируется неявно: нет Copy и Clone, ключ передаётся по ссылке,
+//  - == сравнивает за постоянное время (ct_eq).
+//Байты доступны только через expose_secret, чтобы места, где ключ покидает обёртку, были видны в коде.
+//Режимы и контексты (CbcCipher::new, encrypt_aes192_* и т.д.) принимают &SecretKey<N>; сами блочные шифры
+//(Aes192::new, gost::Kuznyechik::new) и обобщённые режимы над BlockCipher по-прежнему работают с байтами
+use crate::ct_eq;
+use rand::Rng;
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[repr(transparent)]
+pub struct SecretKey<const N: usize>([u8; N]);
+
+impl<const N: usize> SecretKey<N> {
+    //случайный ключ, заполняется сразу внутри обёртки
+    pub fn generate() -> Self {
+        let mut key = SecretKey([0u8; N]);
+        rand::rng().fill(&mut key.0[..]);
+        key
+    }
+
+    //ключ из среза, например из выхода KDF; None, если длина не N
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != N {
+            return None;
+        }
+        let mut key = SecretKey([0u8; N]);
+        key.0.copy_from_slice(bytes);
+        Some(key)
+    }
+
+    pub fn expose_secret(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+//массивы - Copy, поэтому исходный массив у вызывающего остаётся как есть и его нужно затереть самому
+impl<const N: usize> From<[u8; N]> for SecretKey<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        SecretKey(bytes)
+    }
+}
+
+impl<const N: usize> Drop for SecretKey<N> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const N: usize> ZeroizeOnDrop for SecretKey<N> {}
+
+impl<const N: usize> fmt::Debug for SecretKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretKey<{}>(скрыт)", N)
+    }
+}
+
+impl<const N: usize> PartialEq for SecretKey<N> {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+}
+
+impl<const N: usize> Eq for SecretKey<N> {}
+
+//ключевой материал переменной длины с теми же свойствами, например ключ, развёрнутый из KW/KWP
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    pub fn expose_secret(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+//вектор переходит внутрь без копирования
+impl From<Vec<u8>> for SecretBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        SecretBytes(bytes)
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretBytes {}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes({} байт, скрыт)", self.0.len())
+    }
+}
+
+//длина не секретна, ct_eq сравнивает её первой
+impl PartialEq for SecretBytes {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SecretBytes {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::MaybeUninit;
+
+    #[test]
+    fn test_secret_key_debug_and_eq() {
+        let key = SecretKey::from([0x42u8; 24]);
+        assert_eq!(format!("{:?}", key), "SecretKey<24>(скрыт)");
+        assert!(!format!("{:?}", key).contains("66"));
+        assert_eq!(key, SecretKey::from([0x42u8; 24]));
+        assert_ne!(key, SecretKey::generate());
+        assert_eq!(SecretKey::<24>::from_slice(&[0x42; 24]), Some(key));
+        assert_eq!(SecretKey::<24>::from_slice(&[0x42; 23]), None);
+
+        let bytes = SecretBytes::from(vec![0x42u8; 20]);
+        assert_eq!(format!("{:?}", bytes), "SecretBytes(20 байт, скрыт)");
+        assert_eq!(bytes, SecretBytes::from(vec![0x42u8; 20]));
+        assert_ne!(bytes, SecretBytes::from(vec![0x42u8; 21]));
+    }
+
+    //после drop память ключа обнулена
+    #[test]
+    fn test_secret_key_zeroized_on_drop() {
+        let mut slot = MaybeUninit::new(SecretKey::from([0x42u8; 24]));
+        unsafe { slot.assume_init_drop() };
+        let bytes = unsafe { &*(slot.as_ptr() as *const [u8; 24]) };
+        assert_eq!(bytes, &[0u8; 24]);
+    }
+}
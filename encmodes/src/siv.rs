@@ -0,0 +1,150 @@
+//Режим SIV (RFC 5297) - устойчивое к повтору nonce аутентифицированное шифрование
+//Синтетический IV V = S2V(K1, заголовки..., P) вычисляется через OMAC, затем P шифруется CTR на K2 от V
+//Без nonce в заголовках режим детерминирован - так его используют для обёртывания ключей
+use crate::mac::{double_block, omac};
+use crate::{BlockCipher, SecretKey, ct_eq};
+use aes::Aes192;
+use cipher::KeyInit;
+
+fn xor_block(a: &[u8; 16], b: &[u8; 16]) -> [u8; 16] {
+    let mut res = [0u8; 16];
+    for i in 0..16 {
+        res[i] = a[i] ^ b[i];
+    }
+    res
+}
+
+//S2V: D = OMAC(0), D = dbl(D) ^ OMAC(S_i) для всех строк кроме последней, последняя "вмешивается" в D
+pub fn s2v<C: BlockCipher<16>>(cipher: &C, strings: &[&[u8]]) -> [u8; 16] {
+    let Some((last, headers)) = strings.split_last() else {
+        let mut one = [0u8; 16];
+        one[15] = 1;
+        return omac(cipher, &one);
+    };
+    let mut d = omac(cipher, &[0u8; 16]);
+    for s in headers {
+        d = xor_block(&double_block(&d), &omac(cipher, s));
+    }
+    if last.len() >= 16 {
+        //xorend: D XOR-ится в последние 16 байт строки
+        let mut t = last.to_vec();
+        let offset = t.len() - 16;
+        for i in 0..16 {
+            t[offset + i] ^= d[i];
+        }
+        omac(cipher, &t)
+    } else {
+        let mut padded = [0u8; 16];
+        padded[..last.len()].copy_from_slice(last);
+        padded[last.len()] = 0x80;
+        omac(cipher, &xor_block(&double_block(&d), &padded))
+    }
+}
+
+//CTR от V с обнулёнными 31-м и 63-м битами (справа), счётчик - весь 128-битный блок
+fn siv_ctr<C: BlockCipher<16>>(cipher: &C, v: &[u8; 16], data: &[u8]) -> Vec<u8> {
+    let mut q = *v;
+    q[8] &= 0x7f;
+    q[12] &= 0x7f;
+    let mut counter = u128::from_be_bytes(q);
+    let mut res = Vec::with_capacity(data.len());
+    for block in data.chunks(16) {
+        let encrypt_block = cipher.encrypt(&counter.to_be_bytes());
+        res.extend(block.iter().zip(encrypt_block.iter()).map(|(a, b)| a ^ b));
+        counter = counter.wrapping_add(1);
+    }
+    res
+}
+
+//шифрование SIV: на выходе V || C; headers - ассоциированные данные (и, при желании, nonce последним)
+pub fn encrypt_siv<C: BlockCipher<16>>(mac_cipher: &C, ctr_cipher: &C, headers: &[&[u8]], data: &[u8]) -> Vec<u8> {
+    let mut strings = headers.to_vec();
+    strings.push(data);
+    let v = s2v(mac_cipher, &strings);
+    let mut res = v.to_vec();
+    res.extend_from_slice(&siv_ctr(ctr_cipher, &v, data));
+    res
+}
+
+//расшифрование SIV, при несовпадении V возвращает None
+pub fn decrypt_siv<C: BlockCipher<16>>(mac_cipher: &C, ctr_cipher: &C, headers: &[&[u8]], data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 16 {
+        return None;
+    }
+    let v: [u8; 16] = data[..16].try_into().unwrap();
+    let plaintext = siv_ctr(ctr_cipher, &v, &data[16..]);
+    let mut strings = headers.to_vec();
+    strings.push(&plaintext);
+    if !ct_eq(&s2v(mac_cipher, &strings), &v) {
+        return None;
+    }
+    Some(plaintext)
+}
+
+fn aes192_siv_ciphers(key: &SecretKey<48>) -> (Aes192, Aes192) {
+    let key = key.expose_secret();
+    (Aes192::new_from_slice(&key[..24]).unwrap(), Aes192::new_from_slice(&key[24..]).unwrap())
+}
+
+//AES192-SIV: ключ 48 байт = K1 (S2V) || K2 (CTR)
+pub fn encrypt_aes192_siv(data: &[u8], key: &SecretKey<48>, headers: &[&[u8]]) -> Vec<u8> {
+    let (mac_cipher, ctr_cipher) = aes192_siv_ciphers(key);
+    encrypt_siv(&mac_cipher, &ctr_cipher, headers, data)
+}
+
+pub fn decrypt_aes192_siv(data: &[u8], key: &SecretKey<48>, headers: &[&[u8]]) -> Option<Vec<u8>> {
+    let (mac_cipher, ctr_cipher) = aes192_siv_ciphers(key);
+    decrypt_siv(&mac_cipher, &ctr_cipher, headers, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::Aes128;
+
+    fn ciphers(key: &str) -> (Aes128, Aes128) {
+        let key = hex::decode(key).unwrap();
+        let k1: [u8; 16] = key[..16].try_into().unwrap();
+        let k2: [u8; 16] = key[16..].try_into().unwrap();
+        (Aes128::new(&k1.into()), Aes128::new(&k2.into()))
+    }
+
+    //A.1 из RFC 5297 - детерминированный режим
+    #[test]
+    fn test_siv_deterministic_vector() {
+        let (k1, k2) = ciphers("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let ad = hex::decode("101112131415161718191a1b1c1d1e1f2021222324252627").unwrap();
+        let p = hex::decode("112233445566778899aabbccddee").unwrap();
+        let c = encrypt_siv(&k1, &k2, &[&ad], &p);
+        assert_eq!(hex::encode(&c), "85632d07c6e8f37f950acd320a2ecc9340c02b9690c4dc04daef7f6afe5c");
+        assert_eq!(decrypt_siv(&k1, &k2, &[&ad], &c).unwrap(), p);
+    }
+
+    //A.2 из RFC 5297 - два заголовка и nonce
+    #[test]
+    fn test_siv_nonce_based_vector() {
+        let (k1, k2) = ciphers("7f7e7d7c7b7a79787776757473727170404142434445464748494a4b4c4d4e4f");
+        let ad1 = hex::decode("00112233445566778899aabbccddeeffdeaddadadeaddadaffeeddccbbaa99887766554433221100").unwrap();
+        let ad2 = hex::decode("102030405060708090a0").unwrap();
+        let nonce = hex::decode("09f911029d74e35bd84156c5635688c0").unwrap();
+        let p = hex::decode("7468697320697320736f6d6520706c61696e7465787420746f20656e6372797074207573696e67205349562d414553").unwrap();
+        let c = encrypt_siv(&k1, &k2, &[&ad1, &ad2, &nonce], &p);
+        assert_eq!(hex::encode(&c), "7bdb6e3b432667eb06f4d14bff2fbd0fcb900f2fddbe404326601965c889bf17dba77ceb094fa663b7a3f748ba8af829ea64ad544a272e9c485b62a3fd5c0d");
+        assert_eq!(decrypt_siv(&k1, &k2, &[&ad1, &ad2, &nonce], &c).unwrap(), p);
+        assert!(decrypt_siv(&k1, &k2, &[&ad1, &nonce], &c).is_none());
+    }
+
+    #[test]
+    fn test_aes192_siv_key_wrap() {
+        let kek = SecretKey::<48>::generate();
+        let data_key = crate::generate_key();
+        //обёртывание детерминировано: повтор не раскрывает ничего, кроме равенства ключей
+        let wrapped = encrypt_aes192_siv(data_key.expose_secret(), &kek, &[b"data-key-1"]);
+        assert_eq!(wrapped, encrypt_aes192_siv(data_key.expose_secret(), &kek, &[b"data-key-1"]));
+        assert_eq!(wrapped.len(), 16 + 24);
+        assert_eq!(decrypt_aes192_siv(&wrapped, &kek, &[b"data-key-1"]).unwrap(), data_key.expose_secret());
+        let mut broken = wrapped.clone();
+        broken[20] ^= 1;
+        assert!(decrypt_aes192_siv(&broken, &kek, &[b"data-key-1"]).is_none());
+    }
+}
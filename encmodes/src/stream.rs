@@ -0,0 +1,364 @@
+//Потоковое AEAD-шифрование конструкцией STREAM (Hoang, Reyhanitabar, Rogaway, Vizár, "Online
+//Authenticated-Encryption and its Nonce-Reuse Misuse-Resistance", 2015)
+//Данные делятся на сегменты, каждый шифруется AEAD (GCM или OCB) отдельно с nonce
+//  префикс 7 байт || номер сегмента u32 (big-endian) || флаг последнего сегмента (0 или 1)
+//Номер сегмента в nonce обнаруживает перестановку, флаг - обрезку (без последнего сегмента поток не завершить)
+//и дописывание (после последнего сегмента продолжать нельзя). В памяти хранится только один сегмент,
+//так что длина данных не ограничена ничем, кроме 2^32 сегментов
+use crate::{BlockCipher, SecretKey};
+use crate::{gcm, ocb};
+use aes::Aes192;
+use cipher::KeyInit;
+use std::io::{self, Read, Write};
+
+pub const NONCE_PREFIX_LEN: usize = 7;
+pub const TAG_LEN: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AeadMode {
+    Gcm = 1,
+    Ocb = 2,
+}
+
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    //поток кончился до последнего сегмента или посреди сегмента
+    Truncated,
+    //тэг сегмента не сошёлся: неверный ключ, искажённые, переставленные или дописанные сегменты
+    Authentication,
+    //исчерпан номер сегмента
+    CounterOverflow,
+}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+fn segment_nonce(prefix: &[u8; NONCE_PREFIX_LEN], counter: u32, last: bool) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+//шифртекст сегмента || тэг
+fn seal<C: BlockCipher<16>>(cipher: &C, mode: AeadMode, nonce: &[u8; 12], aad: &[u8], data: &[u8]) -> Vec<u8> {
+    let (mut res, tag) = match mode {
+        AeadMode::Gcm => gcm::encrypt_gcm(cipher, nonce, aad, data, TAG_LEN),
+        AeadMode::Ocb => ocb::encrypt_ocb(cipher, nonce, aad, data, TAG_LEN),
+    };
+    res.extend_from_slice(&tag);
+    res
+}
+
+fn open<C: BlockCipher<16>>(cipher: &C, mode: AeadMode, nonce: &[u8; 12], aad: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < TAG_LEN {
+        return None;
+    }
+    let (ciphertext, tag) = data.split_at(data.len() - TAG_LEN);
+    match mode {
+        AeadMode::Gcm => gcm::decrypt_gcm(cipher, nonce, aad, ciphertext, tag),
+        AeadMode::Ocb => ocb::decrypt_ocb(cipher, nonce, aad, ciphertext, tag),
+    }
+}
+
+//шифрование по сегментам: encrypt_next для всех сегментов, кроме последнего, encrypt_last - для последнего;
+//encrypt_last забирает шифратор, так что после последнего сегмента продолжить нельзя
+pub struct StreamEncryptor<C: BlockCipher<16> = Aes192> {
+    cipher: C,
+    mode: AeadMode,
+    prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+}
+
+impl StreamEncryptor {
+    pub fn new(key: &SecretKey<24>, mode: AeadMode, prefix: &[u8; NONCE_PREFIX_LEN]) -> Self {
+        StreamEncryptor::from_cipher(Aes192::new(key.expose_secret().into()), mode, prefix)
+    }
+}
+
+impl<C: BlockCipher<16>> StreamEncryptor<C> {
+    //префикс должен быть уникален для каждого потока на одном ключе
+    pub fn from_cipher(cipher: C, mode: AeadMode, prefix: &[u8; NONCE_PREFIX_LEN]) -> Self {
+        StreamEncryptor { cipher, mode, prefix: *prefix, counter: 0 }
+    }
+
+    pub fn encrypt_next(&mut self, segment: &[u8], aad: &[u8]) -> Result<Vec<u8>, StreamError> {
+        //последнему сегменту тоже нужен номер
+        let next = self.counter.checked_add(1).ok_or(StreamError::CounterOverflow)?;
+        let res = seal(&self.cipher, self.mode, &segment_nonce(&self.prefix, self.counter, false), aad, segment);
+        self.counter = next;
+        Ok(res)
+    }
+
+    pub fn encrypt_last(self, segment: &[u8], aad: &[u8]) -> Vec<u8> {
+        seal(&self.cipher, self.mode, &segment_nonce(&self.prefix, self.counter, true), aad, segment)
+    }
+}
+
+//расшифрование по сегментам в том же порядке; при ошибке тэга открытый текст сегмента не раскрывается
+pub struct StreamDecryptor<C: BlockCipher<16> = Aes192> {
+    cipher: C,
+    mode: AeadMode,
+    prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+}
+
+impl StreamDecryptor {
+    pub fn new(key: &SecretKey<24>, mode: AeadMode, prefix: &[u8; NONCE_PREFIX_LEN]) -> Self {
+        StreamDecryptor::from_cipher(Aes192::new(key.expose_secret().into()), mode, prefix)
+    }
+}
+
+impl<C: BlockCipher<16>> StreamDecryptor<C> {
+    pub fn from_cipher(cipher: C, mode: AeadMode, prefix: &[u8; NONCE_PREFIX_LEN]) -> Self {
+        StreamDecryptor { cipher, mode, prefix: *prefix, counter: 0 }
+    }
+
+    pub fn decrypt_next(&mut self, segment: &[u8], aad: &[u8]) -> Result<Vec<u8>, StreamError> {
+        let next = self.counter.checked_add(1).ok_or(StreamError::CounterOverflow)?;
+        let res = open(&self.cipher, self.mode, &segment_nonce(&self.prefix, self.counter, false), aad, segment)
+            .ok_or(StreamError::Authentication)?;
+        self.counter = next;
+        Ok(res)
+    }
+
+    pub fn decrypt_last(self, segment: &[u8], aad: &[u8]) -> Result<Vec<u8>, StreamError> {
+        open(&self.cipher, self.mode, &segment_nonce(&self.prefix, self.counter, true), aad, segment)
+            .ok_or(StreamError::Authentication)
+    }
+}
+
+//запись потока сегментами по segment_size байт открытого текста (последний короче, возможно пустой);
+//AAD одинаковый для всех сегментов. В конце обязательно вызвать finish, иначе поток будет считаться обрезанным
+pub struct StreamWriter<W: Write, C: BlockCipher<16> = Aes192> {
+    out: W,
+    encryptor: StreamEncryptor<C>,
+    segment_size: usize,
+    aad: Vec<u8>,
+    buf: Vec<u8>,
+}
+
+impl<W: Write, C: BlockCipher<16>> StreamWriter<W, C> {
+    pub fn new(out: W, encryptor: StreamEncryptor<C>, segment_size: usize, aad: &[u8]) -> Self {
+        assert!(segment_size > 0, "размер сегмента должен быть больше нуля");
+        StreamWriter { out, encryptor, segment_size, aad: aad.to_vec(), buf: Vec::with_capacity(segment_size) }
+    }
+
+    //запись последнего сегмента, возвращает нижележащий поток
+    pub fn finish(mut self) -> io::Result<W> {
+        let segment = self.encryptor.encrypt_last(&self.buf, &self.aad);
+        self.out.write_all(&segment)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+impl<W: Write, C: BlockCipher<16>> Write for StreamWriter<W, C> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        //полный сегмент не может быть последним, поэтому пишется сразу
+        while self.buf.len() >= self.segment_size {
+            let segment: Vec<u8> = self.buf.drain(..self.segment_size).collect();
+            let sealed = self
+                .encryptor
+                .encrypt_next(&segment, &self.aad)
+                .map_err(|_| io::Error::other("исчерпан номер сегмента"))?;
+            self.out.write_all(&sealed)?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+//читает, пока буфер не заполнится или поток не кончится; возвращает число прочитанных байт
+pub(crate) fn read_full<R: Read>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+//чтение потока StreamWriter с тем же размером сегмента; последний сегмент узнаётся по тому, что он короче
+pub struct StreamReader<R: Read, C: BlockCipher<16> = Aes192> {
+    input: R,
+    //None после последнего сегмента
+    decryptor: Option<StreamDecryptor<C>>,
+    segment_size: usize,
+    aad: Vec<u8>,
+    //расшифрованный сегмент и позиция в нём для Read
+    plain: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read, C: BlockCipher<16>> StreamReader<R, C> {
+    pub fn new(input: R, decryptor: StreamDecryptor<C>, segment_size: usize, aad: &[u8]) -> Self {
+        assert!(segment_size > 0, "размер сегмента должен быть больше нуля");
+        StreamReader { input, decryptor: Some(decryptor), segment_size, aad: aad.to_vec(), plain: Vec::new(), pos: 0 }
+    }
+
+    //следующий расшифрованный сегмент; None после последнего
+    pub fn next_segment(&mut self) -> Result<Option<Vec<u8>>, StreamError> {
+        let Some(decryptor) = self.decryptor.as_mut() else {
+            return Ok(None);
+        };
+        let mut segment = vec![0u8; self.segment_size + TAG_LEN];
+        let n = read_full(&mut self.input, &mut segment)?;
+        if n < TAG_LEN {
+            return Err(StreamError::Truncated);
+        }
+        //неполный сегмент может быть только последним; последний читается до конца потока,
+        //так что дописанные в конец данные попадают в него и ломают тэг
+        if n == segment.len() {
+            return decryptor.decrypt_next(&segment, &self.aad).map(Some);
+        }
+        segment.truncate(n);
+        self.decryptor.take().unwrap().decrypt_last(&segment, &self.aad).map(Some)
+    }
+}
+
+//Read отдаёт только проверенные сегменты; ошибки проверки - io::ErrorKind::InvalidData
+impl<R: Read, C: BlockCipher<16>> Read for StreamReader<R, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.plain.len() {
+            match self.next_segment() {
+                Ok(Some(segment)) => (self.plain, self.pos) = (segment, 0),
+                Ok(None) => return Ok(0),
+                Err(StreamError::Io(e)) => return Err(e),
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e))),
+            }
+        }
+        let n = buf.len().min(self.plain.len() - self.pos);
+        buf[..n].copy_from_slice(&self.plain[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+//шифрование потока целиком в памяти на AES-192
+pub fn encrypt_aes192_stream(data: &[u8], key: &SecretKey<24>, mode: AeadMode, prefix: &[u8; NONCE_PREFIX_LEN], segment_size: usize, aad: &[u8]) -> Vec<u8> {
+    let mut writer = StreamWriter::new(Vec::new(), StreamEncryptor::new(key, mode, prefix), segment_size, aad);
+    writer.write_all(data).unwrap();
+    writer.finish().unwrap()
+}
+
+pub fn decrypt_aes192_stream(data: &[u8], key: &SecretKey<24>, mode: AeadMode, prefix: &[u8; NONCE_PREFIX_LEN], segment_size: usize, aad: &[u8]) -> Result<Vec<u8>, StreamError> {
+    let mut reader = StreamReader::new(data, StreamDecryptor::new(key, mode, prefix), segment_size, aad);
+    let mut res = Vec::new();
+    while let Some(segment) = reader.next_segment()? {
+        res.extend_from_slice(&segment);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX: [u8; NONCE_PREFIX_LEN] = [1, 2, 3, 4, 5, 6, 7];
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 13 % 256) as u8).collect()
+    }
+
+    #[test]
+    fn test_stream_round_trip() {
+        let key = crate::generate_key();
+        for mode in [AeadMode::Gcm, AeadMode::Ocb] {
+            for len in [0, 1, 63, 64, 65, 128, 1000] {
+                let data = sample(len);
+                let c = encrypt_aes192_stream(&data, &key, mode, &PREFIX, 64, b"aad");
+                //последний сегмент есть всегда, даже пустой
+                assert_eq!(c.len(), len + (len / 64 + 1) * TAG_LEN);
+                assert_eq!(decrypt_aes192_stream(&c, &key, mode, &PREFIX, 64, b"aad").unwrap(), data);
+            }
+        }
+    }
+
+    //сегменты, записанные по частям и через API сегментов, совпадают с записью целиком
+    #[test]
+    fn test_stream_segments_and_writer_agree() {
+        let key = crate::generate_key();
+        let data = sample(300);
+        let whole = encrypt_aes192_stream(&data, &key, AeadMode::Gcm, &PREFIX, 100, b"");
+        let mut writer = StreamWriter::new(Vec::new(), StreamEncryptor::new(&key, AeadMode::Gcm, &PREFIX), 100, b"");
+        for part in data.chunks(7) {
+            writer.write_all(part).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), whole);
+
+        let mut enc = StreamEncryptor::new(&key, AeadMode::Gcm, &PREFIX);
+        let mut manual = Vec::new();
+        for segment in data.chunks(100) {
+            manual.extend(enc.encrypt_next(segment, b"").unwrap());
+        }
+        manual.extend(enc.encrypt_last(b"", b""));
+        assert_eq!(manual, whole);
+
+        let mut out = Vec::new();
+        let mut reader = StreamReader::new(&whole[..], StreamDecryptor::new(&key, AeadMode::Gcm, &PREFIX), 100, b"");
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn test_stream_tampering_detected() {
+        let key = crate::generate_key();
+        let data = sample(300);
+        let c = encrypt_aes192_stream(&data, &key, AeadMode::Ocb, &PREFIX, 100, b"");
+        let segment = 100 + TAG_LEN;
+        let dec = |c: &[u8]| decrypt_aes192_stream(c, &key, AeadMode::Ocb, &PREFIX, 100, b"");
+
+        //обрезка по границе сегмента: последнего сегмента нет
+        assert!(matches!(dec(&c[..3 * segment]), Err(StreamError::Truncated)));
+        assert!(matches!(dec(&c[..2 * segment]), Err(StreamError::Truncated)));
+        //обрезка посреди сегмента: укороченный сегмент принимается за последний, тэг не сходится
+        assert!(matches!(dec(&c[..2 * segment + 50]), Err(StreamError::Authentication)));
+        assert!(matches!(dec(&c[..c.len() - 1]), Err(StreamError::Truncated)));
+        //перестановка сегментов
+        let mut swapped = c[segment..2 * segment].to_vec();
+        swapped.extend_from_slice(&c[..segment]);
+        swapped.extend_from_slice(&c[2 * segment..]);
+        assert!(matches!(dec(&swapped), Err(StreamError::Authentication)));
+        //дописанный в конец сегмент
+        let mut extended = c.clone();
+        extended.extend_from_slice(&c[..segment]);
+        assert!(matches!(dec(&extended), Err(StreamError::Authentication)));
+        //другой AAD или префикс
+        assert!(matches!(decrypt_aes192_stream(&c, &key, AeadMode::Ocb, &PREFIX, 100, b"x"), Err(StreamError::Authentication)));
+        assert!(matches!(decrypt_aes192_stream(&c, &key, AeadMode::Ocb, &[0; 7], 100, b""), Err(StreamError::Authentication)));
+        //промежуточный сегмент нельзя выдать за последний
+        let dec = StreamDecryptor::new(&key, AeadMode::Ocb, &PREFIX);
+        assert!(matches!(dec.decrypt_last(&c[..segment], b""), Err(StreamError::Authentication)));
+        //через Read ошибка приходит как InvalidData, проверенные сегменты до неё отданы
+        let mut reader = StreamReader::new(&swapped[..], StreamDecryptor::new(&key, AeadMode::Ocb, &PREFIX), 100, b"");
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_stream_counter_overflow() {
+        let key = crate::generate_key();
+        let mut enc = StreamEncryptor::new(&key, AeadMode::Gcm, &PREFIX);
+        enc.counter = u32::MAX - 1;
+        assert!(enc.encrypt_next(b"a", b"").is_ok());
+        assert!(matches!(enc.encrypt_next(b"b", b""), Err(StreamError::CounterOverflow)));
+        let last = enc.encrypt_last(b"c", b"");
+        let mut dec = StreamDecryptor::new(&key, AeadMode::Gcm, &PREFIX);
+        dec.counter = u32::MAX;
+        assert_eq!(dec.decrypt_last(&last, b"").unwrap(), b"c");
+    }
+}
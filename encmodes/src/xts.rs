@@ -0,0 +1,225 @@
+//Режим XTS (IEEE 1619) для шифрования секторов диска
+//Ключ состоит из двух половин: K1 шифрует данные, K2 - номер сектора (твик)
+//Длина шифртекста равна длине открытого текста: неполный последний блок обрабатывается кражей шифртекста
+use crate::{BlockCipher, SecretKey};
+use aes::Aes192;
+use cipher::KeyInit;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XtsError {
+    //сектор короче одного блока: кражу шифртекста не из чего сделать
+    //(в том числе неполный последний сектор образа или sector_size < 16)
+    SectorTooShort,
+}
+
+//умножение твика на alpha в GF(2^128); в XTS блок хранится в порядке little-endian
+fn mul_alpha(t: &[u8; 16]) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    let mut carry = 0u8;
+    for i in 0..16 {
+        out[i] = (t[i] << 1) | carry;
+        carry = t[i] >> 7;
+    }
+    out[0] ^= 0x87 & 0u8.wrapping_sub(carry);
+    out
+}
+
+fn xor_block(a: &[u8; 16], b: &[u8; 16]) -> [u8; 16] {
+    let mut res = [0u8; 16];
+    for i in 0..16 {
+        res[i] = a[i] ^ b[i];
+    }
+    res
+}
+
+pub struct Xts<C: BlockCipher<16>> {
+    data_cipher: C,
+    tweak_cipher: C,
+}
+
+impl<C: BlockCipher<16>> Xts<C> {
+    pub fn new(data_cipher: C, tweak_cipher: C) -> Self {
+        Xts { data_cipher, tweak_cipher }
+    }
+
+    //начальный твик T = E_K2(номер сектора в little-endian)
+    fn initial_tweak(&self, sector_index: u64) -> [u8; 16] {
+        let mut i = [0u8; 16];
+        i[..8].copy_from_slice(&sector_index.to_le_bytes());
+        self.tweak_cipher.encrypt(&i)
+    }
+
+    fn encrypt_block(&self, block: &[u8; 16], t: &[u8; 16]) -> [u8; 16] {
+        xor_block(&self.data_cipher.encrypt(&xor_block(block, t)), t)
+    }
+
+    fn decrypt_block(&self, block: &[u8; 16], t: &[u8; 16]) -> [u8; 16] {
+        xor_block(&self.data_cipher.decrypt(&xor_block(block, t)), t)
+    }
+
+    //шифрование одного сектора (не короче 16 байт)
+    pub fn encrypt_sector(&self, data: &[u8], sector_index: u64) -> Result<Vec<u8>, XtsError> {
+        if data.len() < 16 {
+            return Err(XtsError::SectorTooShort);
+        }
+        let full = data.len() / 16;
+        let tail = data.len() % 16;
+        let mut res = Vec::with_capacity(data.len());
+        let mut t = self.initial_tweak(sector_index);
+        //при краже шифртекста последний полный блок обрабатывается отдельно
+        let plain_blocks = if tail == 0 { full } else { full - 1 };
+        for block in data.chunks(16).take(plain_blocks) {
+            res.extend_from_slice(&self.encrypt_block(block.try_into().unwrap(), &t));
+            t = mul_alpha(&t);
+        }
+        if tail != 0 {
+            let last_full: &[u8; 16] = data[plain_blocks * 16..full * 16].try_into().unwrap();
+            let cc = self.encrypt_block(last_full, &t);
+            //PP = неполный блок || хвост CC, C_{m-1} = E(PP), C_m = первые tail байт CC
+            let mut pp = [0u8; 16];
+            pp[..tail].copy_from_slice(&data[full * 16..]);
+            pp[tail..].copy_from_slice(&cc[tail..]);
+            res.extend_from_slice(&self.encrypt_block(&pp, &mul_alpha(&t)));
+            res.extend_from_slice(&cc[..tail]);
+        }
+        Ok(res)
+    }
+
+    //расшифрование одного сектора
+    pub fn decrypt_sector(&self, data: &[u8], sector_index: u64) -> Result<Vec<u8>, XtsError> {
+        if data.len() < 16 {
+            return Err(XtsError::SectorTooShort);
+        }
+        let full = data.len() / 16;
+        let tail = data.len() % 16;
+        let mut res = Vec::with_capacity(data.len());
+        let mut t = self.initial_tweak(sector_index);
+        let plain_blocks = if tail == 0 { full } else { full - 1 };
+        for block in data.chunks(16).take(plain_blocks) {
+            res.extend_from_slice(&self.decrypt_block(block.try_into().unwrap(), &t));
+            t = mul_alpha(&t);
+        }
+        if tail != 0 {
+            //предпоследний блок шифровался на следующем твике
+            let last_full: &[u8; 16] = data[plain_blocks * 16..full * 16].try_into().unwrap();
+            let pp = self.decrypt_block(last_full, &mul_alpha(&t));
+            let mut cc = [0u8; 16];
+            cc[..tail].copy_from_slice(&data[full * 16..]);
+            cc[tail..].copy_from_slice(&pp[tail..]);
+            res.extend_from_slice(&self.decrypt_block(&cc, &t));
+            res.extend_from_slice(&pp[..tail]);
+        }
+        Ok(res)
+    }
+
+    //шифрование образа диска: данные режутся на секторы sector_size байт, нумерация с first_sector;
+    //последний сектор может быть короче sector_size, но не короче 16 байт
+    pub fn encrypt_sectors(&self, data: &[u8], sector_size: usize, first_sector: u64) -> Result<Vec<u8>, XtsError> {
+        if sector_size < 16 {
+            return Err(XtsError::SectorTooShort);
+        }
+        let mut res = Vec::with_capacity(data.len());
+        for (i, sector) in data.chunks(sector_size).enumerate() {
+            res.extend_from_slice(&self.encrypt_sector(sector, first_sector + i as u64)?);
+        }
+        Ok(res)
+    }
+
+    pub fn decrypt_sectors(&self, data: &[u8], sector_size: usize, first_sector: u64) -> Result<Vec<u8>, XtsError> {
+        if sector_size < 16 {
+            return Err(XtsError::SectorTooShort);
+        }
+        let mut res = Vec::with_capacity(data.len());
+        for (i, sector) in data.chunks(sector_size).enumerate() {
+            res.extend_from_slice(&self.decrypt_sector(sector, first_sector + i as u64)?);
+        }
+        Ok(res)
+    }
+}
+
+//XTS-AES192: ключ 48 байт = K1 || K2
+pub fn aes192_xts(key: &SecretKey<48>) -> Xts<Aes192> {
+    let key = key.expose_secret();
+    Xts::new(Aes192::new_from_slice(&key[..24]).unwrap(), Aes192::new_from_slice(&key[24..]).unwrap())
+}
+
+pub fn encrypt_aes192_xts(data: &[u8], key: &SecretKey<48>, sector_index: u64) -> Result<Vec<u8>, XtsError> {
+    aes192_xts(key).encrypt_sector(data, sector_index)
+}
+
+pub fn decrypt_aes192_xts(data: &[u8], key: &SecretKey<48>, sector_index: u64) -> Result<Vec<u8>, XtsError> {
+    aes192_xts(key).decrypt_sector(data, sector_index)
+}
+
+//тестовые векторы из приложения B IEEE 1619 (XTS-AES-128)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::Aes128;
+
+    fn xts128(key1: &str, key2: &str) -> Xts<Aes128> {
+        let k1: [u8; 16] = hex::decode(key1).unwrap().try_into().unwrap();
+        let k2: [u8; 16] = hex::decode(key2).unwrap().try_into().unwrap();
+        Xts::new(Aes128::new(&k1.into()), Aes128::new(&k2.into()))
+    }
+
+    fn check(xts: &Xts<Aes128>, sector: u64, p: &str, c: &str) {
+        let p = hex::decode(p).unwrap();
+        let ciphertext = xts.encrypt_sector(&p, sector).unwrap();
+        assert_eq!(hex::encode(&ciphertext), c);
+        assert_eq!(xts.decrypt_sector(&ciphertext, sector).unwrap(), p);
+    }
+
+    #[test]
+    fn test_xts_full_blocks_vectors() {
+        let zero = "00000000000000000000000000000000";
+        check(&xts128(zero, zero), 0, &"00".repeat(32),
+              "917cf69ebd68b2ec9b9fe9a3eadda692cd43d2f59598ed858c02c2652fbf922e");
+        check(&xts128(&"11".repeat(16), &"22".repeat(16)), 0x3333333333, &"44".repeat(32),
+              "c454185e6a16936e39334038acef838bfb186fff7480adc4289382ecd6d394f0");
+        check(&xts128("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0", &"22".repeat(16)), 0x3333333333, &"44".repeat(32),
+              "af85336b597afc1a900b2eb21ec949d292df4c047e0b21532186a5971a227a89");
+    }
+
+    #[test]
+    fn test_xts_ciphertext_stealing_vectors() {
+        //номер сектора в стандарте записан байтами в порядке little-endian: 9a78563412
+        let xts = xts128("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0", "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0");
+        check(&xts, 0x123456789a, "000102030405060708090a0b0c0d0e0f10", "6c1625db4671522d3d7599601de7ca09ed");
+        check(&xts, 0x123456789a, "000102030405060708090a0b0c0d0e0f1011", "d069444b7a7e0cab09e24447d24deb1fedbf");
+        check(&xts, 0x123456789a, "000102030405060708090a0b0c0d0e0f10111213", "9d84c813f719aa2c7be3f66171c7c5c2edbf9dac");
+    }
+
+    #[test]
+    fn test_aes192_xts_sectors() {
+        let key = SecretKey::<48>::generate();
+        let sector = b"sector contents that are not block aligned!";
+        let ciphertext = encrypt_aes192_xts(sector, &key, 7).unwrap();
+        assert_eq!(ciphertext.len(), sector.len());
+        assert_ne!(ciphertext, encrypt_aes192_xts(sector, &key, 8).unwrap());
+        assert_eq!(decrypt_aes192_xts(&ciphertext, &key, 7).unwrap(), sector);
+
+        //секторы образа можно расшифровывать по отдельности
+        let xts = aes192_xts(&key);
+        let image: Vec<u8> = (0..2048u32).map(|i| (i % 251) as u8).collect();
+        let encrypted = xts.encrypt_sectors(&image, 512, 100).unwrap();
+        assert_eq!(xts.decrypt_sector(&encrypted[1024..1536], 102).unwrap(), &image[1024..1536]);
+        assert_eq!(xts.decrypt_sectors(&encrypted, 512, 100).unwrap(), image);
+    }
+
+    //хвост образа короче блока и слишком маленькие секторы - ошибка, а не паника
+    #[test]
+    fn test_xts_short_sectors_rejected() {
+        let xts = aes192_xts(&SecretKey::<48>::generate());
+        assert_eq!(xts.encrypt_sector(&[0u8; 15], 0), Err(XtsError::SectorTooShort));
+        assert_eq!(xts.decrypt_sector(&[], 0), Err(XtsError::SectorTooShort));
+        let image = vec![0x42u8; 2 * 512 + 7];
+        assert_eq!(xts.encrypt_sectors(&image, 512, 0), Err(XtsError::SectorTooShort));
+        assert_eq!(xts.decrypt_sectors(&image, 512, 0), Err(XtsError::SectorTooShort));
+        assert_eq!(xts.encrypt_sectors(&image[..1024], 8, 0), Err(XtsError::SectorTooShort));
+        //последний сектор из 16 и более байт обрабатывается кражей шифртекста
+        let image = vec![0x42u8; 2 * 512 + 17];
+        let encrypted = xts.encrypt_sectors(&image, 512, 0).unwrap();
+        assert_eq!(xts.decrypt_sectors(&encrypted, 512, 0).unwrap(), image);
+    }
+}
@@ -0,0 +1,32 @@
+//Бенчмарки имитовставок: cargo run --release -- bench [максимальный размер сообщения в байтах]
+//Замер - общий для всех крейтов, см. benchkit
+use crate::*;
+use benchkit::{fmt_size, measure, report_op, report_throughput, sizes, Stats};
+use std::hint::black_box;
+
+pub fn run(args: &[String]) {
+    let key = SecretKey::from([0x55u8; 24]);
+    let (cbc_mac, omac, hmac) = (CbcMac::new(&key), Omac::new(&key), Hmac::new(&key));
+    for size in sizes(args) {
+        let data: Vec<u8> = (0..size).map(|i| i as u8).collect();
+        println!("--- {} ---", fmt_size(size));
+        let report = |name: &str, s: Stats| report_throughput(name, size, &s);
+        let tag = imito_cbc_mac(&key, &data);
+        report("cbc-mac", measure(|| imito_cbc_mac(&key, &data)));
+        report("cbc-mac проверка", measure(|| imito_cbc_mac_verify(&tag, &data, &key)));
+        report("cbc-mac с контекстом", measure(|| cbc_mac.mac(&data)));
+        let tag = imito_omac(&key, &data);
+        report("omac", measure(|| imito_omac(&key, &data)));
+        report("omac проверка", measure(|| imito_omac_verify(&tag, &data, &key)));
+        report("omac с контекстом", measure(|| omac.mac(&data)));
+        let tag = imito_hmac(&key, &data);
+        report("hmac", measure(|| imito_hmac(&key, &data)));
+        report("hmac проверка", measure(|| imito_hmac_verify(&tag, &data, &key)));
+        report("hmac с контекстом", measure(|| hmac.mac(&data)));
+    }
+
+    println!("--- отдельные операции ---");
+    report_op("aes-192 блок", &measure(|| aes192_encrypt_block(&key, black_box(&[0x5a; 16]))));
+    report_op("Omac::new", &measure(|| Omac::new(black_box(&key))));
+    report_op("Hmac::new", &measure(|| Hmac::new(black_box(&key))));
+}
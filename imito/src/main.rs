@@ -1,7 +1,12 @@
+mod bench;
+mod cavp;
+mod secret;
 use aes::Aes192;
 use cipher::{KeyInit, BlockCipherEncrypt, BlockCipherDecrypt};
 use sha2::{Sha256, Digest};
 use hex;
+use zeroize::{Zeroize, Zeroizing};
+pub use secret::SecretKey;
 
 //XOR массивов произвольной длины
 fn xor_arrays<'out>(a: &[u8], b: &[u8], out: &'out mut [u8]) {
@@ -10,17 +15,18 @@ fn xor_arrays<'out>(a: &[u8], b: &[u8], out: &'out mut [u8]) {
         out[i] = a[i] ^ b[i];
     }
 }
-//конкатенация массивов
-fn concat_bytes(a: &[u8], b: &[u8]) -> Vec<u8> {
-    let mut v = Vec::with_capacity(a.len() + b.len());
-    v.extend_from_slice(a);
-    v.extend_from_slice(b);
-    v
+//сравнение без раннего выхода (для проверки имитовставок и SecretKey); длина не секретна
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(diff) == 0
 }
 //генерация ключей для OMAC
-fn gen_keys(key: &[u8; 24]) -> ([u8; 16], [u8; 16]) {
+fn gen_keys(cipher: &Aes192) -> ([u8; 16], [u8; 16]) {
     let zero_block = [0u8; 16];
-    let l = aes192_encrypt_block(key, &zero_block);
+    let l = encrypt_block(cipher, &zero_block);
     let k1 = temp(&l);
     let k2 = temp(&k1);
     (k1, k2)
@@ -40,17 +46,21 @@ fn temp(block: &[u8; 16]) -> [u8; 16] {
     out
 }
 
-//шифрование блока
-pub fn aes192_encrypt_block(key: &[u8; 24], block: &[u8; 16]) -> [u8; 16] {
-    let  cipher = Aes192::new(key.into());
+//шифрование блока уже расширенным ключом
+fn encrypt_block(cipher: &Aes192, block: &[u8; 16]) -> [u8; 16] {
     let mut buf = *block;
     cipher.encrypt_block((&mut buf).into());
-    buf.into()
+    buf
+}
+
+//шифрование блока
+pub fn aes192_encrypt_block(key: &SecretKey<24>, block: &[u8; 16]) -> [u8; 16] {
+    encrypt_block(&Aes192::new(key.expose_secret().into()), block)
 }
 
 //расшифрование блока
-pub fn aes192_decrypt_block(key: &[u8; 24], block: &[u8; 16]) -> [u8; 16] {
-    let  cipher = Aes192::new(key.into());
+pub fn aes192_decrypt_block(key: &SecretKey<24>, block: &[u8; 16]) -> [u8; 16] {
+    let  cipher = Aes192::new(key.expose_secret().into());
     let mut buf = *block;
     cipher.decrypt_block((&mut buf).into());
     buf.into()
@@ -111,109 +121,165 @@ pub fn one_zeros_pad(data: &[u8], block_size: usize) -> Vec<u8> {
     padded
 }
 
-pub fn imito_cbc_mac(key: &[u8; 24] ,data: &[u8]) -> Vec<u8>{
-    let padded_data = one_zeros_pad_zero(data, 16);
-    let mut result = [0u8; 16];
-    for block in padded_data.chunks(16) {
-        let mut res = [0u8; 16];
-
-        let mut block16 = [0u8;16];
-        block16.copy_from_slice(block);
-        xor_arrays(&result, &block16, &mut res);
-
-        result = aes192_encrypt_block(key, &res);
-    }
-    result.to_vec()
+//Контексты имитовставок: ключ AES расширяется (для OMAC ещё и вырабатываются подключи,
+//для HMAC - хешируются ipad и opad) один раз в new, дальше объект используется для любого числа сообщений
+pub struct CbcMac {
+    cipher: Aes192,
 }
-//все верификации одинаковые
-pub fn imito_cbc_mac_verify(imito: &[u8], data: &[u8], key: &[u8; 24]) -> bool{
-    let new_imito = imito_cbc_mac(key, &data);
-    if new_imito == *imito {
-        return true
+
+impl CbcMac {
+    pub fn new(key: &SecretKey<24>) -> Self {
+        CbcMac { cipher: Aes192::new(key.expose_secret().into()) }
     }
-    false
-}
-pub fn imito_omac(key: &[u8; 24] ,data: &[u8]) -> Vec<u8>{
-    let (k1, k2) = gen_keys(key);
-
-    let mut result = [0u8; 16];
-    let blocks: Vec<&[u8]> = data.chunks(16).collect();
-    let n = blocks.len();
-    if n == 0 {
-        // пустое сообщение: единственный блок = pad(0) XOR K2
-        let block = one_zeros_pad(&[], 16);
-        let mut res = [0u8; 16];
-
-        let mut block16 = [0u8;16];
-        block16.copy_from_slice(&block);
-        xor_arrays(&result, &block16, &mut res);
-
-        result = aes192_encrypt_block(key, &res);
-    } else {
-        //обрабатываем все блоки кроме последнего
-        for block in &blocks[..n-1] {
+
+    pub fn mac(&self, data: &[u8]) -> Vec<u8> {
+        let padded_data = one_zeros_pad_zero(data, 16);
+        let mut result = [0u8; 16];
+        for block in padded_data.chunks(16) {
             let mut res = [0u8; 16];
 
             let mut block16 = [0u8;16];
             block16.copy_from_slice(block);
             xor_arrays(&result, &block16, &mut res);
 
-            result = aes192_encrypt_block(key, &res);
+            result = encrypt_block(&self.cipher, &res);
         }
+        result.to_vec()
+    }
 
-        //обрабатываем последний блок
-        let last = blocks[n-1];
-        let mut last_block = [0u8; 16];
+    pub fn verify(&self, imito: &[u8], data: &[u8]) -> bool {
+        ct_eq(&self.mac(data), imito)
+    }
+}
+
+pub struct Omac {
+    cipher: Aes192,
+    k1: [u8; 16],
+    k2: [u8; 16],
+}
+
+//подключи k1, k2 выводятся из ключа и затираются вместе с контекстом (расширенный ключ AES затирает сам aes)
+impl Drop for Omac {
+    fn drop(&mut self) {
+        self.k1.zeroize();
+        self.k2.zeroize();
+    }
+}
 
-        if last.len() == 16 {
-            //если полный блок -> XOR с K1
-            xor_arrays(last, &k1, &mut last_block);
+impl Omac {
+    pub fn new(key: &SecretKey<24>) -> Self {
+        let cipher = Aes192::new(key.expose_secret().into());
+        let (k1, k2) = gen_keys(&cipher);
+        Omac { cipher, k1, k2 }
+    }
+
+    pub fn mac(&self, data: &[u8]) -> Vec<u8> {
+        let mut result = [0u8; 16];
+        let blocks: Vec<&[u8]> = data.chunks(16).collect();
+        let n = blocks.len();
+        if n == 0 {
+            // пустое сообщение: единственный блок = pad(0) XOR K2
+            let block = one_zeros_pad(&[], 16);
+            let mut res = [0u8; 16];
+
+            let mut block16 = [0u8;16];
+            block16.copy_from_slice(&block);
+            xor_arrays(&block16, &self.k2, &mut res);
+
+            result = encrypt_block(&self.cipher, &res);
         } else {
-            //если неполный -> паддинг 10... и XOR с K2
-            let padded = one_zeros_pad(last, 16);
+            //обрабатываем все блоки кроме последнего
+            for block in &blocks[..n-1] {
+                let mut res = [0u8; 16];
+
+                let mut block16 = [0u8;16];
+                block16.copy_from_slice(block);
+                xor_arrays(&result, &block16, &mut res);
 
-            let mut padded16 = [0u8; 16];
-            padded16.copy_from_slice(&padded);
+                result = encrypt_block(&self.cipher, &res);
+            }
 
-            xor_arrays(&padded16, &k2, &mut last_block);
+            //обрабатываем последний блок
+            let last = blocks[n-1];
+            let mut last_block = [0u8; 16];
+
+            if last.len() == 16 {
+                //если полный блок -> XOR с K1
+                xor_arrays(last, &self.k1, &mut last_block);
+            } else {
+                //если неполный -> паддинг 10... и XOR с K2
+                let padded = one_zeros_pad(last, 16);
+
+                let mut padded16 = [0u8; 16];
+                padded16.copy_from_slice(&padded);
+
+                xor_arrays(&padded16, &self.k2, &mut last_block);
+            }
+
+            //последний шаг - CBC-MAC
+            let mut res = [0u8; 16];
+            xor_arrays(&result, &last_block, &mut res);
+            result = encrypt_block(&self.cipher, &res);
         }
+        result.to_vec()
+    }
 
-        //последний шаг - CBC-MAC
-        let mut res = [0u8; 16];
-        xor_arrays(&result, &last_block, &mut res);
-        result = aes192_encrypt_block(key, &res);
+    pub fn verify(&self, imito: &[u8], data: &[u8]) -> bool {
+        ct_eq(&self.mac(data), imito)
     }
-    result.to_vec()
 }
 
-pub fn imito_omac_verify(imito: &[u8], data: &[u8], key: &[u8; 24]) -> bool{
-    let new_imito = imito_omac(key, &data);
-    if new_imito == *imito {
-        return true
+//HMAC-SHA256 с ключом hex(key), как в imito_hmac; хранятся состояния хеша после блоков ipad и opad
+pub struct Hmac {
+    inner: Sha256,
+    outer: Sha256,
+}
+
+impl Hmac {
+    pub fn new(key: &SecretKey<24>) -> Self {
+        //промежуточные копии ключа затираются при выходе
+        let s = Zeroizing::new(hex::encode(key.expose_secret()));
+        let mut xor_ipad = Zeroizing::new([0u8; 64]);
+        let mut xor_opad = Zeroizing::new([0u8; 64]);
+        //генерация ключа
+        let key_plus = Zeroizing::new(key_gen(&s));
+        const IPAD: [u8; 64] = [0x36; 64];
+        const OPAD: [u8; 64] = [0x5C; 64];
+        xor_arrays(&IPAD, &*key_plus, &mut *xor_ipad);
+        xor_arrays(&OPAD, &*key_plus, &mut *xor_opad);
+        Hmac { inner: Sha256::new_with_prefix(*xor_ipad), outer: Sha256::new_with_prefix(*xor_opad) }
+    }
+
+    pub fn mac(&self, data: &[u8]) -> Vec<u8> {
+        let h1 = self.inner.clone().chain_update(data).finalize();
+        self.outer.clone().chain_update(h1).finalize().to_vec()
+    }
+
+    pub fn verify(&self, imito: &[u8], data: &[u8]) -> bool {
+        ct_eq(&self.mac(data), imito)
     }
-    false
 }
 
-pub fn imito_hmac(key: &[u8; 24] ,data: &[u8]) -> Vec<u8> {
-    let s = hex::encode(key);
-    let mut xor_ipad = [0u8; 64];
-    let mut xor_opad = [0u8; 64];
-    //генерация ключа
-    let key_plus = key_gen(&s);
-    const IPAD: [u8; 64] = [0x36; 64];
-    const OPAD: [u8; 64] = [0x5C; 64];
-    xor_arrays(&IPAD, &key_plus, &mut xor_ipad);
-    xor_arrays(&OPAD, &key_plus, &mut xor_opad);
-    let h1 = hasher_sha256(&concat_bytes(&xor_ipad, data));
-    let h2 = hasher_sha256(&concat_bytes(&xor_opad, &h1));
-    h2.to_vec()
+pub fn imito_cbc_mac(key: &SecretKey<24> ,data: &[u8]) -> Vec<u8>{
+    CbcMac::new(key).mac(data)
 }
-pub fn imito_hmac_verify(imito: &[u8], data: &[u8], key: &[u8; 24]) -> bool{
-    let new_imito = imito_hmac(key, &data);
-    if new_imito == *imito {
-        return true
-    }
-    false
+//все верификации одинаковые
+pub fn imito_cbc_mac_verify(imito: &[u8], data: &[u8], key: &SecretKey<24>) -> bool{
+    CbcMac::new(key).verify(imito, data)
+}
+pub fn imito_omac(key: &SecretKey<24> ,data: &[u8]) -> Vec<u8>{
+    Omac::new(key).mac(data)
+}
+
+pub fn imito_omac_verify(imito: &[u8], data: &[u8], key: &SecretKey<24>) -> bool{
+    Omac::new(key).verify(imito, data)
+}
+
+pub fn imito_hmac(key: &SecretKey<24> ,data: &[u8]) -> Vec<u8> {
+    Hmac::new(key).mac(data)
+}
+pub fn imito_hmac_verify(imito: &[u8], data: &[u8], key: &SecretKey<24>) -> bool{
+    Hmac::new(key).verify(imito, data)
 }
 
 
@@ -248,7 +314,20 @@ where
 }
 
 fn main() {
-    let key = [0x55u8; 24];
+    //cargo run -- kat - прогон векторов NIST из vectors/ (см. cavp)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(|s| s.as_str()) == Some("kat") {
+        if !cavp::run_all() {
+            std::process::exit(1);
+        }
+        return;
+    }
+    //cargo run --release -- bench - замеры (см. bench)
+    if args.first().map(|s| s.as_str()) == Some("bench") {
+        bench::run(&args[1..]);
+        return;
+    }
+    let key = SecretKey::from([0x55u8; 24]);
     let message = b"Hello, this is a test message for MAC!";
     let empty_message = b"";
 
@@ -314,4 +393,30 @@ fn main() {
     } else {
         println!("OMAC отклонил поддельный тег — устойчив к атаке!");
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_rejects(tag: Vec<u8>, verify: impl Fn(&[u8]) -> bool) {
+        assert!(verify(&tag));
+        let mut wrong = tag.clone();
+        *wrong.last_mut().unwrap() ^= 1;
+        assert!(!verify(&wrong));
+        assert!(!verify(&tag[..tag.len() - 1]));
+        assert!(!verify(&[]));
+    }
+
+    //неверный и укороченный тэг не проходят проверку ни в одном контексте
+    #[test]
+    fn test_verify_rejects_wrong_and_truncated_tag() {
+        let key = SecretKey::from([0x24u8; 24]);
+        let data = b"attack at dawn";
+        check_rejects(CbcMac::new(&key).mac(data), |t| CbcMac::new(&key).verify(t, data));
+        check_rejects(Omac::new(&key).mac(data), |t| Omac::new(&key).verify(t, data));
+        check_rejects(Hmac::new(&key).mac(data), |t| Hmac::new(&key).verify(t, data));
+        assert!(ct_eq(b"", b""));
+        assert!(!ct_eq(b"ab", b"abc"));
+    }
+}
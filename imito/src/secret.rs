@@ -0,0 +1,70 @@
+//Секретный ключ фиксированной длины N байт (как SecretKey в encmodes, крейты независимы):
+//затирается при удалении, Debug не печатает содержимое, нет Copy и Clone, == за постоянное время.
+//Контексты имитовставок (CbcMac, Omac, Hmac), функции imito_* и aes192_encrypt_block/aes192_decrypt_block
+//принимают &SecretKey<24>
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[repr(transparent)]
+pub struct SecretKey<const N: usize>([u8; N]);
+
+impl<const N: usize> SecretKey<N> {
+    //None, если длина не N
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != N {
+            return None;
+        }
+        let mut key = SecretKey([0u8; N]);
+        key.0.copy_from_slice(bytes);
+        Some(key)
+    }
+
+    pub fn expose_secret(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+//исходный массив у вызывающего не затирается
+impl<const N: usize> From<[u8; N]> for SecretKey<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        SecretKey(bytes)
+    }
+}
+
+impl<const N: usize> Drop for SecretKey<N> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const N: usize> ZeroizeOnDrop for SecretKey<N> {}
+
+impl<const N: usize> fmt::Debug for SecretKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretKey<{}>(скрыт)", N)
+    }
+}
+
+//сравнение без раннего выхода: время не зависит от позиции первого различия
+impl<const N: usize> PartialEq for SecretKey<N> {
+    fn eq(&self, other: &Self) -> bool {
+        crate::ct_eq(&self.0, &other.0)
+    }
+}
+
+impl<const N: usize> Eq for SecretKey<N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_key_debug_and_eq() {
+        let key = SecretKey::from([0x55u8; 24]);
+        assert_eq!(format!("{:?}", key), "SecretKey<24>(скрыт)");
+        assert_eq!(key, SecretKey::from([0x55u8; 24]));
+        assert_ne!(key, SecretKey::from([0x56u8; 24]));
+        assert_eq!(SecretKey::<24>::from_slice(&[0x55; 24]), Some(key));
+        assert_eq!(SecretKey::<24>::from_slice(&[0x55; 25]), None);
+    }
+}
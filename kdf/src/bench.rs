@@ -0,0 +1,25 @@
+//Бенчмарки KDF: cargo run --release -- bench [максимальный размер сообщения в байтах]
+//для hmac по размерам сообщения - МБ/с и такты на байт, для KDF - время операции. Замер - см. benchkit
+use crate::*;
+use benchkit::{measure, report_op, report_throughput, sizes};
+use std::hint::black_box;
+
+pub fn run(args: &[String]) {
+    let key = b"rootrootrootroot";
+    for size in sizes(args) {
+        let data: Vec<u8> = (0..size).map(|i| i as u8).collect();
+        report_throughput("hmac-sha256", size, &measure(|| hmac_sha256(key, &data)));
+    }
+
+    println!("--- KDF ---");
+    report_op("hkdf (32 байта)", &measure(|| hkdf_hmac_sha256(b"stul_ne_skripit", b"pepper_lol", b"contexta_net", 32)));
+    report_op("hkdf (255 байт)", &measure(|| hkdf_hmac_sha256(b"stul_ne_skripit", b"pepper_lol", b"contexta_net", 255)));
+    for iterations in [1000, 10_000, 100_000] {
+        let s = measure(|| pbkdf2_hmac_sha256(b"my_super_password_is_qwerty", b"im_a_super_unique_user", iterations, 32));
+        report_op(&format!("pbkdf2 ({} итераций)", iterations), &s);
+    }
+    report_op("kdf_j", &measure(|| kdf_j(1, key, &str8(0))));
+    //ключ первого сообщения и ключ, для которого пересчитываются все уровни дерева
+    report_op("tlstree (i=0)", &measure(|| tlstree(key, black_box(0))));
+    report_op("tlstree (i=49152)", &measure(|| tlstree(key, black_box(49152))));
+}
@@ -1,5 +1,9 @@
+mod bench;
+mod secret;
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
+use zeroize::Zeroizing;
+pub use secret::SecretBytes;
 pub use pbkdf2::pbkdf2; //ТОЛЬКО ДЛЯ ТЕСТОВ!!!
 //pub use hkdf::Hkdf; //ТОЛЬКО ДЛЯ ТЕСТОВ!!!
 
@@ -9,36 +13,37 @@ pub fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
     mac.finalize().into_bytes().to_vec()
 }
 
-fn hkdf_hmac_sha256(skm: &[u8], xts: &[u8], ctx_info: &[u8], l: usize) -> Vec<u8> {
+fn hkdf_hmac_sha256(skm: &[u8], xts: &[u8], ctx_info: &[u8], l: usize) -> SecretBytes {
     const HASH_LEN: usize = 32;
 
     //extract шаг
-    let prk = hmac_sha256(xts, skm);
+    //промежуточные значения (PRK, T(i)) затираются так же, как результат
+    let prk = Zeroizing::new(hmac_sha256(xts, skm));
     //expand шаг
     let t = (l + HASH_LEN - 1) / HASH_LEN; // округление вверх как раз
     let mut result = Vec::with_capacity(t * HASH_LEN);
-    let mut k_prev = Vec::new();
+    let mut k_prev = Zeroizing::new(Vec::new());
     for i in 1..=t {
         // Собираем вход: T(i-1) || CTXInfo || i
-        let mut input = Vec::new();
+        let mut input = Zeroizing::new(Vec::new());
         input.extend_from_slice(&k_prev);      // добавляем предыдущее k
         input.extend_from_slice(ctx_info);     // добавляем ctx_info
         input.push(i as u8);                   // добавляем i
-        k_prev = hmac_sha256(&prk, &input);
+        k_prev = Zeroizing::new(hmac_sha256(&prk, &input));
         result.extend_from_slice(&k_prev);
     }
 
-    //длина l
+    //длина l; отброшенный хвост затрётся вместе со всей ёмкостью вектора
     result.truncate(l);
-    result
+    SecretBytes::from(result)
 }
 
-fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, vec_len: usize) -> Vec<u8> {
+fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, vec_len: usize) -> SecretBytes {
     const HASH_LEN: usize = 32; // SHA-256
     let dk_len = vec_len;
 
     if dk_len == 0 {
-        return Vec::new();
+        return SecretBytes::from(Vec::new());
     }
     let l = (dk_len + HASH_LEN - 1) / HASH_LEN; //также округление вверх
     let mut result = Vec::with_capacity(l * HASH_LEN);
@@ -47,11 +52,11 @@ fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, vec_len: us
         salt_i.extend_from_slice(salt);
         let i_bytes = (i as u32).to_be_bytes();
         salt_i.extend_from_slice(&i_bytes);
-        let mut u = hmac_sha256(password, &salt_i);
+        let mut u = Zeroizing::new(hmac_sha256(password, &salt_i));
         let mut t = u.clone();
 
         for _ in 1..iterations {
-            u = hmac_sha256(password, &u);
+            u = Zeroizing::new(hmac_sha256(password, &u));
             for (t_byte, u_byte) in t.iter_mut().zip(u.iter()) {
                 *t_byte ^= *u_byte;
             }
@@ -59,10 +64,10 @@ fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, vec_len: us
 
         result.extend_from_slice(&t);
     }
-    result
+    SecretBytes::from(result)
 }
 
-fn kdf_j(j: u8, key: &[u8], d: &[u8]) -> Vec<u8> {
+fn kdf_j(j: u8, key: &[u8], d: &[u8]) -> SecretBytes {
     let mut input = Vec::new();
     input.push(0x01);                     // 0x01
     input.extend_from_slice(b"level");    // level
@@ -72,7 +77,7 @@ fn kdf_j(j: u8, key: &[u8], d: &[u8]) -> Vec<u8> {
     input.push(0x01);                     // 0x01
     input.push(0x00);                     // 0x00
 
-    hmac_sha256(key, &input)
+    SecretBytes::from(hmac_sha256(key, &input))
 }
 fn str8(x: u64) -> [u8; 8] {
     x.to_be_bytes()
@@ -82,11 +87,11 @@ struct TlsTreeDebug {
     d1: [u8; 8],
     d2: [u8; 8],
     d3: [u8; 8],
-    k1: Vec<u8>,
-    k2: Vec<u8>,
-    k3: Vec<u8>,
+    k1: SecretBytes,
+    k2: SecretBytes,
+    k3: SecretBytes,
 }
-fn tlstree_debug(root: &[u8], i: u64) -> (Vec<u8>, TlsTreeDebug) {
+fn tlstree_debug(root: &[u8], i: u64) -> (SecretBytes, TlsTreeDebug) {
     const C1: u16 = 0x69B1;
     const C2: u16 = 0x8040;
     const C3: u16 = 0x4D20;
@@ -95,17 +100,18 @@ fn tlstree_debug(root: &[u8], i: u64) -> (Vec<u8>, TlsTreeDebug) {
     let k1 = kdf_j(1, root, &d1);
 
     let d2 = str8(i & (C2 as u64));
-    let k2 = kdf_j(2, &k1, &d2);
+    let k2 = kdf_j(2, k1.expose_secret(), &d2);
 
     let d3 = str8(i & (C3 as u64));
-    let k3 = kdf_j(3, &k2, &d3);
+    let k3 = kdf_j(3, k2.expose_secret(), &d3);
 
+    //Clone у SecretBytes нет: копия k3 для возврата делается явно
     (
-        k3.clone(),
+        SecretBytes::from(k3.expose_secret().to_vec()),
         TlsTreeDebug { d1, d2, d3, k1, k2, k3 },
     )
 }
-fn tlstree(root: &[u8], i: u64) -> Vec<u8> {
+fn tlstree(root: &[u8], i: u64) -> SecretBytes {
     // Константы из Р 1323565.1.030—2020
     const C1: u16 = 0x69B1;
     const C2: u16 = 0x8040;
@@ -117,11 +123,11 @@ fn tlstree(root: &[u8], i: u64) -> Vec<u8> {
 
     // STR8(i & C2)
     let d2 = str8(i & (C2 as u64));
-    let k2 = kdf_j(2, &k1, &d2);
+    let k2 = kdf_j(2, k1.expose_secret(), &d2);
 
     // STR8(i & C3)
     let d3 = str8(i & (C3 as u64));
-    let k3 = kdf_j(3, &k2, &d3);
+    let k3 = kdf_j(3, k2.expose_secret(), &d3);
     k3
 }
 //так как надо для различных длин проверить + разные константы для tlstree, то тут удобнее будет через тесты
@@ -148,8 +154,8 @@ mod tests {
         assert_eq!(k3.len(), len3);
 
         //первые 16 байт k2 и k3 должны совпадать с k1
-        assert_eq!(&k2[..len1], &k1[..]);
-        assert_eq!(&k3[..len1], &k1[..]);
+        assert_eq!(&k2.expose_secret()[..len1], k1.expose_secret());
+        assert_eq!(&k3.expose_secret()[..len1], k1.expose_secret());
     }
 
     #[test]
@@ -228,7 +234,7 @@ mod tests {
 
         pbkdf2::<Hmac<Sha256>>(password, salt, iterations, &mut output).expect("invalid_len");
 
-        assert_eq!(derived, output);
+        assert_eq!(derived.expose_secret(), output);
     }
     #[test]
     fn test_tlstree_conditions() {
@@ -263,28 +269,43 @@ mod tests {
         assert_eq!(k_a, k_a2);
         assert_eq!(dbg_a.k3, dbg_a2.k3);
     }
+
+    #[test]
+    fn test_derived_key_debug_hidden() {
+        let k = hkdf_hmac_sha256(b"secret1", b"saltA", b"ctx1", 32);
+        let hex_key = hex::encode(k.expose_secret());
+        assert_eq!(format!("{:?}", k), "SecretBytes(32 байт, скрыт)");
+        assert!(!format!("{:?}", k).contains(&hex_key[..8]));
+        assert_ne!(k, hkdf_hmac_sha256(b"secret1", b"saltA", b"ctx1", 16));
+    }
 }
 
 
 fn main() {
+    //cargo run --release -- bench - замеры (см. bench)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(|s| s.as_str()) == Some("bench") {
+        bench::run(&args[1..]);
+        return;
+    }
     println!("hkdf");
     let skm = b"stul_ne_skripit";
     let xts = b"pepper_lol";
     let ctx = b"contexta_net";
     let hkdf_key = hkdf_hmac_sha256(skm, xts, ctx, 32);
-    println!("HKDF:               {}", hex::encode(&hkdf_key));
+    println!("HKDF:               {}", hex::encode(hkdf_key.expose_secret()));
 
     println!("PBKDF2");
     let password = b"my_super_password_is_qwerty";
     let salt = b"im_a_super_unique_user";
     let pbkdf2_key = pbkdf2_hmac_sha256(password, salt, 333, 32);
-    println!("PBKDF2:             {}", hex::encode(&pbkdf2_key));
+    println!("PBKDF2:             {}", hex::encode(pbkdf2_key.expose_secret()));
 
     println!("TLSTREE");
     let root_key = b"rootrootrootroot";
     let i_values = [0u64, 1, 32768, 49152];
     for &i in &i_values {
         let tlstree_key = tlstree(root_key, i);
-        println!("TLSTREE(i={:>6}) = {}", i, hex::encode(&tlstree_key));
+        println!("TLSTREE(i={:>6}) = {}", i, hex::encode(tlstree_key.expose_secret()));
     }
 }
@@ -0,0 +1,55 @@
+//Выработанный ключ произвольной длины: результат hkdf, pbkdf2, kdf_j и tlstree.
+//Затирается при удалении, Debug не печатает содержимое, нет Clone, == за постоянное время.
+//Байты доступны только через expose_secret
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    pub fn expose_secret(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+//вектор переходит внутрь без копирования
+impl From<Vec<u8>> for SecretBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        SecretBytes(bytes)
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretBytes {}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes({} байт, скрыт)", self.0.len())
+    }
+}
+
+//длина не секретна; содержимое сравнивается без раннего выхода
+impl PartialEq for SecretBytes {
+    fn eq(&self, other: &Self) -> bool {
+        if self.0.len() != other.0.len() {
+            return false;
+        }
+        let diff = self.0.iter().zip(&other.0).fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        std::hint::black_box(diff) == 0
+    }
+}
+
+impl Eq for SecretBytes {}
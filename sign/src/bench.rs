@@ -0,0 +1,31 @@
+//Бенчмарки RSA и Рабина: cargo run --release -- bench. Замер - см. benchkit
+//Генерация ключей ищет случайные простые, поэтому её разброс заметно больше остальных
+use crate::rabin::Rabin;
+use crate::rsa::Rsa;
+use benchkit::{report_op, Stats};
+
+//генерация ключей: разброс в разы, поэтому выборок больше, чем в encmodes
+const MIN_SAMPLES: u128 = 20;
+
+fn measure<T, F: FnMut() -> T>(f: F) -> Stats {
+    benchkit::measure_with_min_samples(MIN_SAMPLES, f)
+}
+
+pub fn run() {
+    let message = b"This is so secret!";
+    //l - длина каждого из простых, модуль вдвое длиннее
+    for l in [256, 512, 1024] {
+        println!("--- модуль {} бит ---", 2 * l);
+        report_op("rsa KGen", &measure(|| Rsa::KGen(l)));
+        let rsa = Rsa::KGen(l);
+        let sig = rsa.sign(message).unwrap();
+        report_op("rsa sign", &measure(|| rsa.sign(message)));
+        report_op("rsa verify", &measure(|| rsa.verify(message, &sig)));
+
+        report_op("rabin KGen", &measure(|| Rabin::KGen(l)));
+        let rabin = Rabin::KGen(l);
+        let c = rabin.Enc(message).unwrap();
+        report_op("rabin Enc", &measure(|| rabin.Enc(message)));
+        report_op("rabin Dec", &measure(|| rabin.Dec(&c)));
+    }
+}
@@ -1,8 +1,10 @@
+mod bench;
 mod rsa;
 mod rabin;
+mod secret;
 use num_bigint::BigUint;
 use rand::RngCore;
-use rsa::{Rsa, RsaPublicKey, RsaSecretKey};
+use rsa::{Rsa, RsaPublicKey};
 use rabin::Rabin;
 //сертификат: (ID, публичный ключ, подпись УЦ)
 #[derive(Clone)]
@@ -37,14 +39,7 @@ pub fn user_generate_request(user_rsa: &Rsa) -> CertRequest {
 pub fn ca_issue_certificate(request: &CertRequest, ca_rsa: &Rsa) -> Option<Certificate> {
     //проверка подписи пользователя
     let pk_bytes = serialize_pk(&request.pk_user);
-    let user_verifier = Rsa {
-        pk: request.pk_user.clone(),
-        sk: RsaSecretKey {
-            n: BigUint::from(1u32),
-            d: BigUint::from(1u32),
-        },
-    };
-    if !user_verifier.verify(&pk_bytes, &request.sigma_user) {
+    if !request.pk_user.verify(&pk_bytes, &request.sigma_user) {
         return None; //неверная подпись
     }
 
@@ -74,17 +69,16 @@ pub fn user_verify_certificate(cert: &Certificate, ca_pk: &RsaPublicKey) -> bool
     let mut msg = cert.id.as_bytes().to_vec();
     msg.extend_from_slice(&serialize_pk(&cert.pk));
 
-    let ca_verifier = Rsa {
-        pk: ca_pk.clone(),
-        sk: RsaSecretKey {
-            n: BigUint::from(1u32),
-            d: BigUint::from(1u32),
-        },
-    };
-    ca_verifier.verify(&msg, &cert.sigma_ca)
+    ca_pk.verify(&msg, &cert.sigma_ca)
 }
 
 fn main() {
+    //cargo run --release -- bench - замеры (см. bench)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(|s| s.as_str()) == Some("bench") {
+        bench::run();
+        return;
+    }
     //тестирование RSA
     println!("RSA test:");
     let rsa = Rsa::KGen(512);
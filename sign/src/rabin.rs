@@ -1,6 +1,8 @@
 use num_bigint::{BigInt, BigUint, RandBigInt, ToBigInt};
 use num_traits::{One, Zero};
 use rand::RngCore;
+use std::fmt;
+use crate::secret::{ct_eq, wipe};
 
 pub struct Rabin {
     pub vk: RabinPublicKey,
@@ -10,10 +12,33 @@ pub struct RabinPublicKey {
     pub n: BigUint,
 }
 
+//секретный ключ: поля закрыты, нет Clone, p и q затираются при удалении, Debug их не печатает
 pub struct RabinSecretKey {
-    pub p: BigUint,
-    pub q: BigUint,
+    p: BigUint,
+    q: BigUint,
 }
+
+impl Drop for RabinSecretKey {
+    fn drop(&mut self) {
+        wipe(&mut self.p);
+        wipe(&mut self.q);
+    }
+}
+
+impl fmt::Debug for RabinSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RabinSecretKey(скрыт)")
+    }
+}
+
+impl PartialEq for RabinSecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        //& вместо &&: второе сравнение выполняется всегда
+        ct_eq(&self.p, &other.p) & ct_eq(&self.q, &other.q)
+    }
+}
+
+impl Eq for RabinSecretKey {}
 #[allow(warnings)]
 impl Rabin {
     pub fn KGen(l: usize) -> Self{
@@ -148,4 +173,22 @@ fn crt(a: &BigUint, p: &BigUint, b: &BigUint, q: &BigUint, n: &BigUint) -> BigUi
     let term1 = a * q * &q_inv;
     let term2 = b * p * &p_inv;
     (term1 + term2) % n
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_key_debug_and_eq() {
+        let rabin = Rabin::KGen(128);
+        let debug = format!("{:?}", rabin.sk);
+        assert_eq!(debug, "RabinSecretKey(скрыт)");
+        for x in [&rabin.sk.p, &rabin.sk.q] {
+            assert!(!debug.contains(&x.to_string()) && !debug.contains(&x.to_str_radix(16)));
+        }
+        let same = RabinSecretKey { p: rabin.sk.p.clone(), q: rabin.sk.q.clone() };
+        assert_eq!(rabin.sk, same);
+        let swapped = RabinSecretKey { p: rabin.sk.q.clone(), q: rabin.sk.p.clone() };
+        assert_ne!(rabin.sk, swapped);
+    }
+}
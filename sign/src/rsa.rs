@@ -3,9 +3,10 @@ use num_traits::identities::One;
 use num_bigint::{BigInt, BigUint, RandBigInt, ToBigInt};
 use rand::RngCore;
 use sha2::{Digest, Sha256};
+use std::fmt;
+use crate::secret::{ct_eq, wipe};
 
 //структуры для хранения
-#[derive(Clone)]
 pub struct Rsa {
     pub pk: RsaPublicKey,
     pub sk: RsaSecretKey,
@@ -15,10 +16,42 @@ pub struct RsaPublicKey {
     pub n: BigUint,
     pub e: BigUint,
 }
-#[derive(Clone)]
+//секретный ключ: поля закрыты, нет Clone, d затирается при удалении, Debug не печатает d
 pub struct RsaSecretKey {
-    pub n: BigUint,
-    pub d: BigUint,
+    n: BigUint,
+    d: BigUint,
+}
+
+impl Drop for RsaSecretKey {
+    fn drop(&mut self) {
+        wipe(&mut self.d);
+    }
+}
+
+impl fmt::Debug for RsaSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RsaSecretKey({} бит, скрыт)", self.n.bits())
+    }
+}
+
+impl PartialEq for RsaSecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.n == other.n && ct_eq(&self.d, &other.d)
+    }
+}
+
+impl Eq for RsaSecretKey {}
+
+impl RsaPublicKey {
+    //проверка подписи только по открытому ключу
+    pub fn verify(&self, message: &[u8], sigma_bytes: &[u8]) -> bool {
+        let sigma = BigUint::from_bytes_be(sigma_bytes);
+        let y_prime = sigma.modpow(&self.e, &self.n);
+
+        let hash = Sha256::digest(message);
+        let y = BigUint::from_bytes_be(&hash);
+        y_prime == y
+    }
 }
 #[allow(warnings)]
 impl Rsa {
@@ -26,17 +59,21 @@ impl Rsa {
     pub fn KGen(l: usize) -> Self {
         let mut rng = rand::thread_rng();
         //простые p и q
-        let p = Self::generate_prime(l, &mut rng);
-        let q = Self::generate_prime(l, &mut rng);
+        let mut p = Self::generate_prime(l, &mut rng);
+        let mut q = Self::generate_prime(l, &mut rng);
         //модуль и функция эйлера
         let n = &p * &q;
-        let phi = (&p - 1u32) * (&q - 1u32);
+        let mut phi = (&p - 1u32) * (&q - 1u32);
         let mut e = BigUint::from(65537u32);
         if BigUint::from(65537u32) > phi {
             eprintln!("очень маленькие простые числа, e решено выбрать трём");
             e = BigUint::from(3u32);
         }
         let d = modinv(&e, &phi).expect("должен существовать обратный");
+        //p, q и phi раскрывают d, затираются сразу
+        wipe(&mut p);
+        wipe(&mut q);
+        wipe(&mut phi);
 
         //вывод ключей
         let pk = RsaPublicKey { n: n.clone(), e };
@@ -61,13 +98,7 @@ impl Rsa {
 
     //верификация
     pub fn verify(&self, message: &[u8], sigma_bytes: &[u8]) -> bool {
-
-        let sigma = BigUint::from_bytes_be(sigma_bytes);
-        let y_prime = sigma.modpow(&self.pk.e, &self.pk.n);
-
-        let hash = Sha256::digest(message);
-        let y = BigUint::from_bytes_be(&hash);
-        y_prime == y
+        self.pk.verify(message, sigma_bytes)
     }
 
     //генерация простого числа заданной длины
@@ -150,4 +181,20 @@ pub fn modinv(a: &BigUint, m: &BigUint) -> Option<BigUint> {
     }
 
     Some(t.to_biguint().unwrap())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Debug не содержит ни десятичной, ни шестнадцатеричной записи d; сравнение - по n и d
+    #[test]
+    fn test_secret_key_debug_and_eq() {
+        let rsa = Rsa::KGen(128);
+        let debug = format!("{:?}", rsa.sk);
+        assert!(debug.starts_with("RsaSecretKey(") && debug.ends_with("скрыт)"));
+        assert!(!debug.contains(&rsa.sk.d.to_string()) && !debug.contains(&rsa.sk.d.to_str_radix(16)));
+        let same = RsaSecretKey { n: rsa.sk.n.clone(), d: rsa.sk.d.clone() };
+        assert_eq!(rsa.sk, same);
+        assert_ne!(rsa.sk, RsaSecretKey { n: rsa.sk.n.clone(), d: &rsa.sk.d + 1u32 });
+    }
+}
@@ -0,0 +1,53 @@
+//Общее для секретных ключей RSA и Рабина: затирание и сравнение за постоянное время.
+//num-bigint не умеет zeroize, поэтому затирание делается перезаписью цифр нулями на месте:
+//assign_from_slice очищает вектор цифр и заполняет его заново в том же буфере, пока хватает ёмкости;
+//normalize затем отбрасывает нулевые цифры и может освободить буфер, но он к этому моменту уже затёрт
+use num_bigint::BigUint;
+use std::hint::black_box;
+
+//затирание числа; после вызова оно равно нулю
+pub(crate) fn wipe(x: &mut BigUint) {
+    //столько же 64-битных цифр, сколько у числа, чтобы не было перевыделения
+    let zeros = vec![0u32; 2 * x.iter_u64_digits().len()];
+    x.assign_from_slice(black_box(&zeros));
+    black_box(x);
+}
+
+//сравнение без раннего выхода: проходятся все цифры более длинного числа
+pub(crate) fn ct_eq(a: &BigUint, b: &BigUint) -> bool {
+    let len = a.iter_u32_digits().len().max(b.iter_u32_digits().len());
+    let diff = padded(a, len).zip(padded(b, len)).fold(0u32, |acc, (x, y)| acc | (x ^ y));
+    black_box(diff) == 0
+}
+
+//цифры числа, дополненные нулями до len
+fn padded(x: &BigUint, len: usize) -> impl Iterator<Item = u32> + '_ {
+    x.iter_u32_digits().chain(std::iter::repeat(0)).take(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ct_eq_different_lengths() {
+        let zero = BigUint::from(0u32);
+        let big = BigUint::from(1u32) << 64;
+        assert!(!ct_eq(&zero, &big));
+        assert!(!ct_eq(&big, &zero));
+        assert!(ct_eq(&big, &(BigUint::from(1u32) << 64)));
+        assert!(ct_eq(&zero, &BigUint::from(0u32)));
+        //совпадают младшие цифры, отличается только старшая
+        assert!(!ct_eq(&BigUint::from(5u32), &(BigUint::from(5u32) + (BigUint::from(1u32) << 100))));
+    }
+
+    #[test]
+    fn test_wipe_leaves_zero() {
+        let mut x = BigUint::from_bytes_be(&[0xab; 64]);
+        wipe(&mut x);
+        assert_eq!(x, BigUint::from(0u32));
+        let mut zero = BigUint::from(0u32);
+        wipe(&mut zero);
+        assert_eq!(zero, BigUint::from(0u32));
+    }
+}